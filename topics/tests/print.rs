@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+///
+/// Run `topics print` against one good + one bad document
+///
+fn print(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_topics"))
+        .current_dir(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../fixtures/partial"))
+        .arg("print")
+        .args(args)
+        .args(&["good.md", "bad.md"])
+        .output()
+        .expect("topics runs")
+}
+
+#[test]
+fn test_print_json_with_a_bad_doc() {
+    let output = print(&["-p", "json"]);
+    assert_eq!(output.status.code(), Some(1));
+    // a single document on stdout, made up of the problems
+    let diagnostics: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("one JSON document");
+    assert_eq!(diagnostics.as_array().map(Vec::len), Some(1));
+    assert_eq!(diagnostics[0]["code"], "E105");
+}
+
+#[test]
+fn test_print_md_with_a_bad_doc() {
+    let output = print(&["-p", "md"]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    assert!(stdout.contains("say hello"));
+    assert!(!stdout.contains("default impl"));
+    let stderr = String::from_utf8(output.stderr).expect("utf8");
+    assert!(stderr.contains("bad.md"));
+}
+
+#[test]
+fn test_print_html_with_a_bad_doc() {
+    let out_dir = std::env::temp_dir().join("topics-print-html-with-a-bad-doc");
+    let _ = std::fs::remove_dir_all(&out_dir);
+    let output = print(&["-p", "html", "--out-dir", &out_dir.display().to_string()]);
+    assert_eq!(output.status.code(), Some(0));
+    let stderr = String::from_utf8(output.stderr).expect("utf8");
+    assert!(stderr.contains("bad.md"));
+    let pages = std::fs::read_dir(&out_dir)
+        .expect("pages written")
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "html"))
+        .count();
+    assert!(pages > 0);
+}
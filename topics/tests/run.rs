@@ -0,0 +1,14 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn test_run_prints_markdown_instructions() {
+    let output = Command::new(env!("CARGO_BIN_EXE_topics"))
+        .current_dir(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../fixtures/run"))
+        .args(&["run", "Get access", "instructions.md"])
+        .output()
+        .expect("topics runs");
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    assert!(stdout.contains("Ask the **platform** team for an account on the staging server."));
+}
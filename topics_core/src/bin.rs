@@ -3,10 +3,13 @@ use multi_doc::{MultiDoc, SingleDoc};
 use topics_core::cwd::Cwd;
 use topics_core::doc_src::MdDocSource;
 use topics_core::items::{
-    Command, DependencyCheck, Env, FileExistsCheck, HostEntriesCheck, HostEntry, Instruction, Item,
-    ItemWrap, LineMarker, TaskGroup, Topic,
+    Command, Config, DependencyCheck, Env, FileExistsCheck, HostEntriesCheck, HostEntry,
+    Instruction, Item, ItemWrap, LineMarker, TaskGroup, Topic, ValuesFrom,
+};
+use topics_core::{
+    ConfigRefError, CycleError, CycleHop, DefinitionSite, DuplicateNameError, MissingRefError,
+    Output, SerializedError, ShellSyntaxError, UnsetVarError,
 };
-use topics_core::{CycleError, Output, SerializedError};
 use typescript_definitions::TypeScriptifyTrait;
 
 fn main() {
@@ -23,10 +26,19 @@ fn main() {
     println!("{}", MultiDoc::type_script_ify());
     println!("{}", SingleDoc::type_script_ify());
     println!("{}", CycleError::type_script_ify());
+    println!("{}", CycleHop::type_script_ify());
+    println!("{}", MissingRefError::type_script_ify());
+    println!("{}", DuplicateNameError::type_script_ify());
+    println!("{}", DefinitionSite::type_script_ify());
+    println!("{}", ConfigRefError::type_script_ify());
+    println!("{}", ShellSyntaxError::type_script_ify());
+    println!("{}", UnsetVarError::type_script_ify());
     println!("{}", DependencyCheck::type_script_ify());
     println!("{}", HostEntriesCheck::type_script_ify());
     println!("{}", HostEntry::type_script_ify());
     println!("{}", TaskGroup::type_script_ify());
     println!("{}", Env::type_script_ify());
+    println!("{}", ValuesFrom::type_script_ify());
+    println!("{}", Config::type_script_ify());
     println!("{}", Cwd::type_script_ify());
 }
@@ -0,0 +1,39 @@
+use crate::cli::{SubCommand, SubCommandError, SubCommandResult};
+use crate::context::Context;
+use crate::explain::{explain, EXPLANATIONS};
+use crate::print::{PlainPrinter, Print};
+
+#[derive(Debug, Clone, structopt::StructOpt)]
+pub struct ExplainCmd {
+    /// An error code, eg: `E001` or `001`. Lists every code when omitted
+    #[structopt(name = "CODE")]
+    pub code: Option<String>,
+}
+
+impl SubCommand for ExplainCmd {
+    fn exec(&self, ctx: &Context) -> SubCommandResult<()> {
+        let code = match &self.code {
+            Some(code) => code,
+            None => {
+                for explanation in EXPLANATIONS {
+                    println!("{}  {}", explanation.code, explanation.title());
+                }
+                return Ok(());
+            }
+        };
+        match explain(code) {
+            Some(explanation) => {
+                print!("{}", explanation.text);
+                Ok(())
+            }
+            None => {
+                let msg = format!(
+                    "`{}` is not a known error code, run `topics explain` to list them all",
+                    code
+                );
+                let _ = PlainPrinter.print_error(&msg, ctx);
+                Err(SubCommandError::Handled)
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::cli::{SubCommand, SubCommandError, SubCommandResult};
+use crate::context::Context;
+use crate::db::with_db;
+use crate::doc::Doc;
+use crate::graph::{graph_export, GraphFormat};
+use crate::print::{OutputKind, Print};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, structopt::StructOpt)]
+pub struct GraphCmd {
+    #[structopt(short, long, default_value)]
+    pub print_kind: OutputKind,
+
+    /// `dot` for Graphviz, or `mermaid`
+    #[structopt(short, long, default_value = "dot")]
+    pub format: GraphFormat,
+
+    /// Only include the items that this one refers to, directly or not
+    #[structopt(short, long)]
+    pub root: Option<String>,
+
+    /// Files, directories or globs to process
+    #[structopt(name = "FILE", parse(from_os_str))]
+    pub files: Vec<PathBuf>,
+}
+
+impl SubCommand for GraphCmd {
+    fn exec(&self, ctx: &Context) -> SubCommandResult<()> {
+        let files = match ctx.discover(&self.files) {
+            Ok(files) => files,
+            Err(err) => {
+                let _ = self.print_kind.print_error(&err.to_string(), ctx);
+                return Err(SubCommandError::Handled);
+            }
+        };
+        let (good, bad) = ctx.read_docs_split(&files);
+        // documents with problems are reported, then left out of the graph
+        if !bad.is_empty() {
+            let _ = self.print_kind.print_errors(&bad, &ctx);
+        }
+        if good.is_empty() && !bad.is_empty() {
+            return Err(SubCommandError::Handled);
+        }
+        if good.is_empty() {
+            let err = SubCommandError::Empty;
+            let _ = self.print_kind.print_error(&err.to_string(), &ctx);
+            return Err(err);
+        }
+
+        let docs = good
+            .into_iter()
+            .map(|doc| doc.expect("guarded previously"))
+            .collect::<Vec<Doc>>();
+
+        // graph errors are not fatal here, cycles + missing references are drawn instead
+        let result = with_db(&docs, |db, _errors| {
+            graph_export(db, self.root.as_deref()).map(|export| match self.format {
+                GraphFormat::Dot => export.to_dot(),
+                GraphFormat::Mermaid => export.to_mermaid(),
+            })
+        });
+
+        match result {
+            Err(graph_error) => {
+                let _ = self.print_kind.print_error(&graph_error.to_string(), &ctx);
+                Err(SubCommandError::Handled)
+            }
+            Ok(output) => {
+                print!("{}", output);
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::cli::{SubCommand, SubCommandError, SubCommandResult};
+use crate::context::Context;
+use crate::lsp::{serve, Workspace};
+use std::path::PathBuf;
+
+///
+/// Run a language server over stdin/stdout, for editors
+///
+#[derive(Debug, Clone, structopt::StructOpt)]
+pub struct LspCmd {
+    /// Files, directories or globs to process, files opened in the editor are added as they're opened
+    #[structopt(name = "FILE", parse(from_os_str))]
+    pub files: Vec<PathBuf>,
+}
+
+impl SubCommand for LspCmd {
+    fn exec(&self, ctx: &Context) -> SubCommandResult<()> {
+        // stdout belongs to the editor, so errors can only go to stderr
+        let files = match ctx.discover(&self.files) {
+            Ok(files) => files,
+            Err(err) => {
+                eprintln!("{}", err);
+                return Err(SubCommandError::Handled);
+            }
+        };
+        let mut workspace = Workspace::new(files.iter().map(|pb| ctx.join_path(pb)).collect());
+        let stdin = std::io::stdin();
+        let stdout = std::io::stdout();
+        serve(&mut workspace, ctx, &mut stdin.lock(), &mut stdout.lock()).map_err(|err| {
+            eprintln!("{}", err);
+            SubCommandError::Handled
+        })
+    }
+}
@@ -1,9 +1,19 @@
+pub mod explain_cmd;
 pub mod generate_cmd;
+pub mod graph_cmd;
+pub mod lsp_cmd;
+pub mod plan_cmd;
 pub mod print_cmd;
+pub mod run_cmd;
 pub mod sub_command;
 pub mod verify_cmd;
 
+pub use explain_cmd::*;
 pub use generate_cmd::*;
+pub use graph_cmd::*;
+pub use lsp_cmd::*;
+pub use plan_cmd::*;
 pub use print_cmd::*;
+pub use run_cmd::*;
 pub use sub_command::*;
 pub use verify_cmd::*;
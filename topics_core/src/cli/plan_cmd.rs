@@ -0,0 +1,84 @@
+use crate::cli::{SubCommand, SubCommandError, SubCommandResult};
+use crate::context::Context;
+use crate::db::{split_warnings, with_db};
+use crate::doc::Doc;
+use crate::plan::plan;
+use crate::print::{OutputKind, PlainPrinter, Print};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, structopt::StructOpt)]
+pub struct PlanCmd {
+    /// `plain` for a numbered list, `md` for a checklist, or `json`
+    #[structopt(short, long, default_value)]
+    pub print_kind: OutputKind,
+
+    /// The name of a Topic to plan, can be given more than once
+    #[structopt(short, long = "topic", required = true)]
+    pub topics: Vec<String>,
+
+    /// Files, directories or globs to process, every item the Topics refer to must be in one of these
+    #[structopt(name = "FILE", parse(from_os_str))]
+    pub files: Vec<PathBuf>,
+}
+
+impl SubCommand for PlanCmd {
+    fn exec(&self, ctx: &Context) -> SubCommandResult<()> {
+        // stdout is reserved for the plan, so problems are always reported as plain text on stderr
+        let errors = PlainPrinter;
+        let files = match ctx.discover(&self.files) {
+            Ok(files) => files,
+            Err(err) => {
+                let _ = errors.print_error(&err.to_string(), ctx);
+                return Err(SubCommandError::Handled);
+            }
+        };
+        let (good, bad) = ctx.read_docs_split(&files);
+        // documents with problems are reported, then left out of the graph
+        if !bad.is_empty() {
+            let _ = errors.print_errors(&bad, ctx);
+        }
+        if good.is_empty() && !bad.is_empty() {
+            return Err(SubCommandError::Handled);
+        }
+        if good.is_empty() {
+            let err = SubCommandError::Empty;
+            let _ = errors.print_error(&err.to_string(), ctx);
+            return Err(err);
+        }
+
+        let docs = good
+            .into_iter()
+            .map(|doc| doc.expect("guarded previously"))
+            .collect::<Vec<Doc>>();
+
+        let result = with_db(&docs, |db, db_errors| {
+            let (db_errors, warnings) = split_warnings(db_errors);
+            let _ = errors.print_warnings(&warnings, ctx);
+            if !db_errors.is_empty() {
+                return Err(db_errors);
+            }
+            Ok(plan(db, &self.topics).map(|plan| match self.print_kind {
+                OutputKind::Markdown => plan.to_markdown(),
+                OutputKind::Json | OutputKind::Sarif => plan
+                    .to_json()
+                    .expect("items can always be serialized to json"),
+                OutputKind::Plain | OutputKind::Html => plan.to_plain(),
+            }))
+        });
+
+        match result {
+            Err(db_errors) => {
+                let _ = errors.print_db_errors(&db_errors, ctx);
+                Err(SubCommandError::Handled)
+            }
+            Ok(Err(plan_error)) => {
+                let _ = errors.print_error(&plan_error.to_string(), ctx);
+                Err(SubCommandError::Handled)
+            }
+            Ok(Ok(output)) => {
+                println!("{}", output.trim_end());
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,10 +1,16 @@
+use crate::cli::print_outcome;
 use crate::cli::{SubCommand, SubCommandError, SubCommandResult};
 use crate::context::Context;
-use crate::db::try_from_docs;
-use crate::doc::Doc;
+use crate::db::{fuzzy_matches, split_warnings, try_from_docs, with_db, Db};
+use crate::doc::{Doc, DocResult};
 use crate::html_template::HtmlTemplate;
-use crate::print::{OutputKind, Print};
+use crate::items::{Item, Topic};
+use crate::print::{doc_for, topics, OutputKind, PlainPrinter, Print};
+use crate::run::{execute, plan_order};
+use crate::verify::verify_check;
+use crate::watch::{summary, watch};
 use crate::Outputs;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -13,61 +19,153 @@ pub struct PrintCmd {
     #[structopt(short, long, default_value)]
     pub print_kind: OutputKind,
 
+    /// Print a single Topic, by its position in the loaded files (starting at 0)
     #[structopt(short, long)]
     pub index: Option<usize>,
 
+    /// Print every Topic, instead of choosing from a list. Implied when stdin is not a terminal
     #[structopt(short, long)]
     pub all: bool,
 
-    /// Files to process, you should likely include everything here
+    /// Directory to write generated pages into.
+    /// Markdown is printed to stdout when this is omitted, HTML defaults to `__generated__`
+    #[structopt(long, parse(from_os_str))]
+    pub out_dir: Option<PathBuf>,
+
+    /// Keep running, and print again every time one of the files changes.
+    /// Every HTML page is generated again, but only those whose content changed are written
+    #[structopt(short, long)]
+    pub watch: bool,
+
+    /// Files, directories or globs to process.
+    /// Defaults to the `include` globs from `topics.toml` when omitted
     #[structopt(name = "FILE", parse(from_os_str))]
     pub files: Vec<PathBuf>,
 }
 
 impl SubCommand for PrintCmd {
     fn exec(&self, ctx: &Context) -> SubCommandResult<()> {
-        let (good, bad) = ctx.read_docs_split(&self.files);
-        if !bad.is_empty() {
-            let _ = self.print_kind.print_errors(&bad, &ctx);
-            return Err(SubCommandError::Unknown);
-        }
-        if good.is_empty() {
-            let err = SubCommandError::Empty;
-            let _ = self.print_kind.print_error(&err.to_string(), &ctx);
-            return Err(err);
+        let files = match ctx.discover(&self.files) {
+            Ok(files) => files,
+            Err(err) => {
+                let _ = self.print_kind.print_error(&err.to_string(), ctx);
+                return Err(SubCommandError::Handled);
+            }
+        };
+        // the content of every file written so far, so that unchanged pages can be skipped
+        let mut written: HashMap<PathBuf, String> = HashMap::new();
+        if self.watch {
+            watch(&files, ctx, |docs, bad| {
+                summary(&self.print_docs(docs, bad, ctx, &mut written))
+            });
+            return Ok(());
         }
 
+        let (good, bad) = ctx.read_docs_split(&files);
         let docs = good
             .into_iter()
             .map(|doc| doc.expect("guarded previously"))
             .collect::<Vec<Doc>>();
 
-        let outputs = try_from_docs(&docs, &self.print_kind).expect("try_from_docs");
-        match outputs {
-            Outputs::Plain(plain_output) => {
-                // for doc in plain_output.docs {
-                //     eprintln!("item={:#?}", doc);
-                // }
-                // for item in plain_output.items {
-                //     eprintln!("item={:#?}", item);
-                // }
-                // for e in plain_output.errors {
-                //     eprintln!("e={:?}", e);
-                // }
+        self.print_docs(&docs, &bad, ctx, &mut written)
+    }
+}
+
+impl PrintCmd {
+    fn print_docs(
+        &self,
+        docs: &[Doc],
+        bad: &[DocResult<Doc>],
+        ctx: &Context,
+        written: &mut HashMap<PathBuf, String>,
+    ) -> SubCommandResult<()> {
+        // documents with problems are reported, then left out of the graph
+        match self.print_kind {
+            // stdout only ever holds a single document, so the problems become that document
+            OutputKind::Json | OutputKind::Sarif if !bad.is_empty() => {
+                let _ = self.print_kind.print_all_errors(bad, &[], ctx);
+                return Err(SubCommandError::Handled);
+            }
+            _ if !bad.is_empty() => {
+                let _ = self.print_kind.print_errors(bad, ctx);
             }
+            _ => {}
+        }
+        if docs.is_empty() && !bad.is_empty() {
+            return Err(SubCommandError::Handled);
+        }
+        if docs.is_empty() {
+            let err = SubCommandError::Empty;
+            let _ = self.print_kind.print_error(&err.to_string(), ctx);
+            return Err(err);
+        }
+
+        if self.print_kind == OutputKind::Plain {
+            return self.exec_plain(docs, ctx);
+        }
+
+        let outputs = try_from_docs(docs, &self.print_kind).expect("try_from_docs");
+        match outputs {
+            // handled above, since topics are printed from the graph directly
+            Outputs::Plain(_) => {}
             Outputs::Json(json_output) => {
                 let json =
                     serde_json::to_string_pretty(&json_output).expect("serde_json::to_string");
                 println!("{}", json);
             }
+            Outputs::Markdown(md_output) => {
+                let _ = self.print_kind.print_warnings(&md_output.warnings, ctx);
+                if !md_output.errors.is_empty() {
+                    let _ = self.print_kind.print_db_errors(&md_output.errors, ctx);
+                    return Err(SubCommandError::Handled);
+                }
+                match &self.out_dir {
+                    None => print!("{}", md_output.as_single_doc()),
+                    Some(out_dir) => {
+                        let md_output_dir = ctx.opts.cwd.join(out_dir);
+                        let index = md_output.index_page();
+                        let fs_job = fs::create_dir_all(&md_output_dir).and_then(|()| {
+                            for md_page in md_output.pages.iter().chain(Some(&index)) {
+                                let page_path = md_output_dir.join(&md_page.pb);
+                                fs::write(&page_path, &md_page.content)?;
+                                println!("file written... {}", page_path.display());
+                            }
+                            Ok(())
+                        });
+                        if let Err(e) = fs_job {
+                            eprintln!("Couldn't write file");
+                            eprintln!("{}", e.to_string());
+                            return Err(SubCommandError::Handled);
+                        }
+                    }
+                }
+            }
             Outputs::Html(html_output) => {
-                let html_output_dir = ctx.opts.cwd.join("__generated__");
+                let _ = self.print_kind.print_warnings(&html_output.warnings, ctx);
+                if !html_output.errors.is_empty() {
+                    let _ = self.print_kind.print_db_errors(&html_output.errors, ctx);
+                    return Err(SubCommandError::Handled);
+                }
+                let html_output_dir = ctx.opts.cwd.join(
+                    self.out_dir
+                        .as_deref()
+                        .unwrap_or_else(|| "__generated__".as_ref()),
+                );
+                if let Err(e) = fs::create_dir_all(&html_output_dir) {
+                    eprintln!("Couldn't create directory {}", html_output_dir.display());
+                    eprintln!("{}", e.to_string());
+                    return Err(SubCommandError::Handled);
+                }
                 for html_page in &html_output.pages {
                     let page_path = html_output_dir.join(&html_page.pb);
-                    let page_str = html_page.template(&ctx);
+                    let page_str = html_page.template(ctx);
                     match page_str {
-                        Ok(string) => match fs::write(&page_path, string) {
-                            Ok(f) => println!("file written... {}", page_path.display()),
+                        Ok(string) if written.get(&page_path) == Some(&string) => {}
+                        Ok(string) => match fs::write(&page_path, &string) {
+                            Ok(_) => {
+                                println!("file written... {}", page_path.display());
+                                written.insert(page_path, string);
+                            }
                             Err(e) => {
                                 eprintln!("Couldn't write file");
                                 eprintln!("{}", e.to_string());
@@ -80,6 +178,9 @@ impl SubCommand for PrintCmd {
                 }
                 for asset in &html_output.assets {
                     let asset_path = html_output_dir.join(&asset.pb);
+                    if written.get(&asset_path) == asset.content.as_ref() {
+                        continue;
+                    }
                     let parent = asset_path.parent().expect("must have file parent");
                     let fs_job = fs::create_dir_all(parent).and_then(|()| {
                         fs::write(
@@ -89,7 +190,12 @@ impl SubCommand for PrintCmd {
                     });
 
                     match fs_job {
-                        Ok(_f) => println!("file written... {}", asset_path.display()),
+                        Ok(_f) => {
+                            println!("file written... {}", asset_path.display());
+                            if let Some(content) = &asset.content {
+                                written.insert(asset_path, content.clone());
+                            }
+                        }
                         Err(e) => {
                             eprintln!("Couldn't write file");
                             eprintln!("{}", e.to_string());
@@ -99,43 +205,148 @@ impl SubCommand for PrintCmd {
             }
         }
 
-        // if let Err(e) = db {
-        //     let _ = self.print_kind.print_error(&e.to_string(), &ctx);
-        //     return Err(SubCommandError::Handled);
-        // }
-        //
-        // let db = db.expect("previously guarded");
-        //
-        // if self.all {
-        //     let _ = self.print_kind.print_all(&docs, &db, &ctx);
-        //     return Ok(());
-        // }
-        //
-        // // if we get here, files were valid, but NO topics were selected
-        // let _ = self.print_kind.print_welcome(&docs, &ctx);
-        //
-        // let titles = docs
-        //     .iter()
-        //     .map(|doc| doc.topic_names())
-        //     .flatten()
-        //     .collect::<Vec<&str>>();
-        //
-        // use dialoguer::MultiSelect;
-        //
-        // // ctx.print_welcome(&good, &ctx);
-        // let selections = MultiSelect::new()
-        //     .items(&titles)
-        //     .interact()
-        //     .map_err(|_| SubCommandError::Unknown)?;
-        //
-        // for title in selections.iter().map(|idx| titles[*idx]) {
-        //     for doc in &docs {
-        //         if let Some(topic) = doc.topic_by_name(&title) {
-        //             let _ = self.print_kind.print_topic(&topic, &db, &doc, &ctx);
-        //         }
-        //     }
-        // }
-        //
         Ok(())
     }
+
+    fn exec_plain(&self, docs: &[Doc], ctx: &Context) -> SubCommandResult<()> {
+        let printer = PlainPrinter;
+        let result = with_db(docs, |db, errors| {
+            let (errors, warnings) = split_warnings(errors);
+            let _ = printer.print_warnings(&warnings, ctx);
+            if !errors.is_empty() {
+                return Err(errors);
+            }
+            let all_topics = topics(db);
+            // there's nobody to answer the prompts while watching, or when stdin
+            // isn't a terminal, so everything is printed instead
+            let interactive =
+                !self.all && self.index.is_none() && !self.watch && atty::is(atty::Stream::Stdin);
+            let selected = match (self.all, self.index) {
+                (true, _) | (false, None) if !interactive => all_topics,
+                (false, Some(index)) => match all_topics.get(index) {
+                    Some(topic) => vec![*topic],
+                    None => {
+                        return Ok(Err(format!(
+                            "--index {} is out of range, {} Topic(s) were found",
+                            index,
+                            all_topics.len()
+                        )))
+                    }
+                },
+                (false, None) => {
+                    let _ = printer.print_welcome(docs, ctx);
+                    match pick_topics(&all_topics) {
+                        Ok(selected) => selected,
+                        Err(e) => return Ok(Err(e.to_string())),
+                    }
+                }
+            };
+            for topic in &selected {
+                if let Some(doc) = doc_for(topic, docs, db) {
+                    let _ = printer.print_topic(topic, db, doc, ctx);
+                }
+            }
+            if interactive && !selected.is_empty() {
+                return Ok(next_action(db, &selected, ctx));
+            }
+            Ok(Ok(()))
+        });
+
+        match result {
+            Err(db_errors) => {
+                let _ = printer.print_db_errors(&db_errors, ctx);
+                Err(SubCommandError::Handled)
+            }
+            Ok(Err(msg)) => {
+                let _ = printer.print_error(&msg, ctx);
+                Err(SubCommandError::Handled)
+            }
+            Ok(Ok(())) => Ok(()),
+        }
+    }
+}
+
+///
+/// Narrow the Topics down with a fuzzy search, then choose any number of the matches
+///
+fn pick_topics<'a>(all_topics: &[&'a Topic]) -> std::io::Result<Vec<&'a Topic>> {
+    use dialoguer::{Input, MultiSelect};
+    let names = all_topics
+        .iter()
+        .map(|topic| topic.name.item.clone())
+        .collect::<Vec<String>>();
+    if names.is_empty() {
+        eprintln!("No Topics were found, so there is nothing to choose from");
+        return Ok(vec![]);
+    }
+    loop {
+        let query: String = Input::new()
+            .with_prompt("Search Topics (leave empty to list them all)")
+            .allow_empty(true)
+            .interact_text()?;
+        let matches = fuzzy_matches(&query, &names);
+        if matches.is_empty() {
+            eprintln!("No Topics match `{}`, try again", query);
+            continue;
+        }
+        let labels = matches
+            .iter()
+            .map(|index| names[*index].as_str())
+            .collect::<Vec<&str>>();
+        let chosen = MultiSelect::new()
+            .with_prompt("Select with <space>, confirm with <enter>")
+            .items(&labels)
+            .interact()?;
+        return Ok(chosen
+            .into_iter()
+            .map(|index| all_topics[matches[index]])
+            .collect());
+    }
+}
+
+///
+/// Offer to `run` the chosen Topics, or `verify` the Dependency Checks they need
+///
+fn next_action(db: &Db, selected: &[&Topic], ctx: &Context) -> Result<(), String> {
+    let choices = &[
+        "Run them",
+        "Verify their Dependency Checks",
+        "Nothing, exit",
+    ];
+    let choice = dialoguer::Select::new()
+        .with_prompt("What next?")
+        .items(choices)
+        .default(choices.len() - 1)
+        .interact()
+        .map_err(|e| e.to_string())?;
+    let names = selected
+        .iter()
+        .map(|topic| topic.name.item.as_str())
+        .collect::<Vec<&str>>();
+    let items = match choice {
+        0 | 1 => plan_order(db, &names).map_err(|e| e.to_string())?,
+        _ => return Ok(()),
+    };
+    if choice == 0 {
+        let completed = execute(db, &items, ctx).map_err(|e| e.to_string())?;
+        PlainPrinter.print_heading("Done", &format!("{} step(s) completed", completed));
+        return Ok(());
+    }
+    let mut broken = 0;
+    let mut total = 0;
+    for item in items {
+        if let Item::DependencyCheck(dc) = item {
+            let outcome = verify_check(dc, ctx, None);
+            print_outcome(dc, &outcome);
+            total += 1;
+            if !outcome.is_ok() {
+                broken += 1;
+            }
+        }
+    }
+    if broken > 0 {
+        return Err(format!("{} of {} check(s) failed", broken, total));
+    }
+    PlainPrinter.print_heading("Done", &format!("{} check(s) passed", total));
+    Ok(())
 }
@@ -0,0 +1,80 @@
+use crate::cli::{SubCommand, SubCommandError, SubCommandResult};
+use crate::context::Context;
+use crate::db::{split_warnings, with_db};
+use crate::doc::Doc;
+use crate::print::{OutputKind, Print};
+use crate::run::{execute, run_order};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, structopt::StructOpt)]
+pub struct RunCmd {
+    #[structopt(short, long, default_value)]
+    pub print_kind: OutputKind,
+
+    /// The name of the Topic to run
+    #[structopt(name = "TOPIC")]
+    pub topic: String,
+
+    /// Files, directories or globs to process, every item the Topic refers to must be in one of these
+    #[structopt(name = "FILE", parse(from_os_str))]
+    pub files: Vec<PathBuf>,
+}
+
+impl SubCommand for RunCmd {
+    fn exec(&self, ctx: &Context) -> SubCommandResult<()> {
+        let files = match ctx.discover(&self.files) {
+            Ok(files) => files,
+            Err(err) => {
+                let _ = self.print_kind.print_error(&err.to_string(), ctx);
+                return Err(SubCommandError::Handled);
+            }
+        };
+        let (good, bad) = ctx.read_docs_split(&files);
+        // documents with problems are reported, then left out of the graph
+        if !bad.is_empty() {
+            let _ = self.print_kind.print_errors(&bad, &ctx);
+        }
+        if good.is_empty() && !bad.is_empty() {
+            return Err(SubCommandError::Handled);
+        }
+        if good.is_empty() {
+            let err = SubCommandError::Empty;
+            let _ = self.print_kind.print_error(&err.to_string(), &ctx);
+            return Err(err);
+        }
+
+        let docs = good
+            .into_iter()
+            .map(|doc| doc.expect("guarded previously"))
+            .collect::<Vec<Doc>>();
+
+        let result = with_db(&docs, |db, errors| {
+            // nothing is run when the graph has problems, since the order may be wrong
+            let (errors, warnings) = split_warnings(errors);
+            let _ = self.print_kind.print_warnings(&warnings, &ctx);
+            if !errors.is_empty() {
+                return Err(errors);
+            }
+            let run = run_order(db, &self.topic).and_then(|items| execute(db, &items, &ctx));
+            Ok(run)
+        });
+
+        match result {
+            Err(db_errors) => {
+                let _ = self.print_kind.print_db_errors(&db_errors, &ctx);
+                Err(SubCommandError::Handled)
+            }
+            Ok(Err(run_error)) => {
+                let _ = self.print_kind.print_error(&run_error.to_string(), &ctx);
+                Err(SubCommandError::Handled)
+            }
+            Ok(Ok(completed)) => {
+                self.print_kind.print_heading(
+                    "Done",
+                    &format!("{} step(s) completed for `{}`", completed, self.topic),
+                );
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::cli::{GenerateCmd, PrintCmd, VerifyCmd};
+use crate::cli::{ExplainCmd, GenerateCmd, GraphCmd, LspCmd, PlanCmd, PrintCmd, RunCmd, VerifyCmd};
 use crate::context::Context;
 
 pub trait SubCommand {
@@ -22,4 +22,9 @@ pub enum SubCommandItems {
     Print(PrintCmd),
     Generate(GenerateCmd),
     Verify(VerifyCmd),
+    Run(RunCmd),
+    Graph(GraphCmd),
+    Plan(PlanCmd),
+    Lsp(LspCmd),
+    Explain(ExplainCmd),
 }
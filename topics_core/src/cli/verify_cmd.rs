@@ -1,30 +1,227 @@
 use crate::cli::{SubCommand, SubCommandError, SubCommandResult};
 use crate::context::Context;
 
+use crate::db::{split_warnings, with_db};
+use crate::doc::{Doc, DocResult};
+use crate::items::DependencyCheck;
 use crate::print::{OutputKind, Print};
+use crate::verify::{binaries, dependency_checks, missing_binaries, verify_check, VerifyOutcome};
+use crate::watch::{summary, watch};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, structopt::StructOpt)]
 #[structopt(alias = "g")]
 pub struct VerifyCmd {
-    #[structopt(short, long, default_value)]
+    /// `json` or `sarif` print problems in a machine readable form, on stdout
+    #[structopt(short, long, alias = "format", default_value)]
     pub print_kind: OutputKind,
 
+    /// Run the `verify` script of every Dependency Check. Without this, only the
+    /// documents themselves are checked
+    #[structopt(long)]
+    pub run: bool,
+
+    /// Offer to run the `autofix` script of any Dependency Check that fails, implies --run
+    #[structopt(long)]
+    pub fix: bool,
+
+    /// Run every `autofix` script without asking first, implies --fix
+    #[structopt(short, long)]
+    pub yes: bool,
+
+    /// Instead of running checks, ensure every program that scripts invoke can be found on the PATH
+    #[structopt(long)]
+    pub binaries: bool,
+
+    /// Keep running, and check the documents again every time one of the files changes.
+    /// Scripts are never run while watching, so --run, --fix and --yes are ignored
+    #[structopt(short, long)]
+    pub watch: bool,
+
+    /// Files, directories or globs to process.
+    /// Defaults to the `include` globs from `topics.toml` when omitted
     #[structopt(name = "files")]
     files: Vec<PathBuf>,
 }
 
 impl SubCommand for VerifyCmd {
     fn exec(&self, ctx: &Context) -> SubCommandResult<()> {
-        let (good, bad) = ctx.read_docs_split(&self.files);
-        if !bad.is_empty() {
-            let _ = self.print_kind.print_errors(&bad, &ctx);
-            return Err(SubCommandError::Unknown);
+        let files = match ctx.discover(&self.files) {
+            Ok(files) => files,
+            Err(err) => {
+                let _ = self.print_kind.print_error(&err.to_string(), ctx);
+                return Err(SubCommandError::Handled);
+            }
+        };
+        if self.watch {
+            // nobody is there to answer prompts, and scripts shouldn't run on every save
+            let validate = VerifyCmd {
+                run: false,
+                fix: false,
+                yes: false,
+                ..self.clone()
+            };
+            watch(&files, ctx, |docs, bad| {
+                summary(&validate.verify_docs(docs, bad, ctx))
+            });
+            return Ok(());
         }
-        println!("good={}", good.len());
-        println!("bad={}", bad.len());
-        // dbg!(bad);
-        // dbg!(good);
-        Ok(())
+
+        let (good, bad) = ctx.read_docs_split(&files);
+        let docs = good
+            .into_iter()
+            .map(|doc| doc.expect("guarded previously"))
+            .collect::<Vec<Doc>>();
+
+        self.verify_docs(&docs, &bad, ctx)
     }
 }
+
+impl VerifyCmd {
+    fn verify_docs(
+        &self,
+        docs: &[Doc],
+        bad: &[DocResult<Doc>],
+        ctx: &Context,
+    ) -> SubCommandResult<()> {
+        // documents with problems are left out, the rest are still checked
+        if docs.is_empty() && !bad.is_empty() {
+            let _ = self.print_kind.print_errors(bad, ctx);
+            return Err(SubCommandError::Handled);
+        }
+        if docs.is_empty() {
+            let err = SubCommandError::Empty;
+            let _ = self.print_kind.print_error(&err.to_string(), ctx);
+            return Err(err);
+        }
+
+        if self.binaries {
+            return self.exec_binaries(docs, bad, ctx);
+        }
+
+        let ask = |dc: &DependencyCheck| {
+            dialoguer::Confirm::new()
+                .with_prompt(format!("`{}` failed, run its autofix?", dc.name.item))
+                .default(true)
+                .interact()
+                .unwrap_or(false)
+        };
+        let always = |_: &DependencyCheck| true;
+        let confirm: Option<&dyn Fn(&DependencyCheck) -> bool> = match (self.fix, self.yes) {
+            (_, true) => Some(&always),
+            (true, false) => Some(&ask),
+            (false, false) => None,
+        };
+
+        let run = self.run || self.fix || self.yes;
+
+        let result = with_db(docs, |db, errors| {
+            let (errors, warnings) = split_warnings(errors);
+            if !errors.is_empty() {
+                // reported together, so that `json` + `sarif` output is a single document
+                return Err(errors.into_iter().chain(warnings).collect::<Vec<_>>());
+            }
+            let checks = dependency_checks(db);
+            if !run {
+                return Ok((checks.len(), None, warnings));
+            }
+            let outcomes = checks
+                .into_iter()
+                .map(|dc| {
+                    let outcome = verify_check(dc, ctx, confirm);
+                    print_outcome(dc, &outcome);
+                    outcome
+                })
+                .collect::<Vec<VerifyOutcome>>();
+            Ok((outcomes.len(), Some(outcomes), warnings))
+        });
+
+        match result {
+            Err(db_errors) => {
+                let _ = self.print_kind.print_all_errors(bad, &db_errors, ctx);
+                Err(SubCommandError::Handled)
+            }
+            Ok((_, _, warnings)) if !bad.is_empty() => {
+                let _ = self.print_kind.print_all_errors(bad, &warnings, ctx);
+                Err(SubCommandError::Handled)
+            }
+            Ok((total, None, warnings)) => {
+                let _ = self.print_kind.print_valid(&warnings, ctx);
+                let msg = format!(
+                    "{} document(s) valid, {} Dependency Check(s) not run (use --run)",
+                    docs.len(),
+                    total
+                );
+                self.print_kind.print_heading("Done", &msg);
+                Ok(())
+            }
+            Ok((_, Some(outcomes), warnings)) => {
+                let broken = outcomes.iter().filter(|outcome| !outcome.is_ok()).count();
+                if broken > 0 {
+                    let _ = self.print_kind.print_warnings(&warnings, ctx);
+                    let msg = format!("{} of {} check(s) failed", broken, outcomes.len());
+                    let _ = self.print_kind.print_error(&msg, ctx);
+                    return Err(SubCommandError::Handled);
+                }
+                let _ = self.print_kind.print_valid(&warnings, ctx);
+                self.print_kind
+                    .print_heading("Done", &format!("{} check(s) passed", outcomes.len()));
+                Ok(())
+            }
+        }
+    }
+
+    fn exec_binaries(
+        &self,
+        docs: &[Doc],
+        bad: &[DocResult<Doc>],
+        ctx: &Context,
+    ) -> SubCommandResult<()> {
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let result = with_db(docs, |db, errors| {
+            let (errors, warnings) = split_warnings(errors);
+            if !errors.is_empty() {
+                // reported together, so that `json` + `sarif` output is a single document
+                return Err(errors.into_iter().chain(warnings).collect::<Vec<_>>());
+            }
+            Ok((binaries(db).len(), missing_binaries(db, &path), warnings))
+        });
+
+        match result {
+            Err(db_errors) => {
+                let _ = self.print_kind.print_all_errors(bad, &db_errors, ctx);
+                Err(SubCommandError::Handled)
+            }
+            Ok((total, missing, warnings)) => {
+                use ansi_term::Colour::Red;
+                for binary in &missing {
+                    eprintln!("{} {}", Red.bold().paint("✗"), binary);
+                }
+                if !bad.is_empty() {
+                    let _ = self.print_kind.print_all_errors(bad, &warnings, ctx);
+                    return Err(SubCommandError::Handled);
+                }
+                if !missing.is_empty() {
+                    let _ = self.print_kind.print_warnings(&warnings, ctx);
+                    let msg = format!("{} of {} program(s) not found", missing.len(), total);
+                    let _ = self.print_kind.print_error(&msg, ctx);
+                    return Err(SubCommandError::Handled);
+                }
+                let _ = self.print_kind.print_valid(&warnings, ctx);
+                self.print_kind
+                    .print_heading("Done", &format!("{} program(s) found", total));
+                Ok(())
+            }
+        }
+    }
+}
+
+pub(crate) fn print_outcome(dc: &DependencyCheck, outcome: &VerifyOutcome) {
+    use ansi_term::Colour::{Cyan, Green, Red};
+    let status = if outcome.is_ok() {
+        Green.bold().paint("✓")
+    } else {
+        Red.bold().paint("✗")
+    };
+    eprintln!("{} {}: {}", status, Cyan.paint(&dc.name.item), outcome);
+}
@@ -1,3 +1,4 @@
+use crate::discover::{discover, DiscoverError};
 use crate::doc::{Doc, DocResult};
 use crate::opt::Opt;
 use std::path::PathBuf;
@@ -20,6 +21,12 @@ impl Context {
     pub fn join_path(&self, pb: impl Into<PathBuf>) -> PathBuf {
         self.opts.cwd.join(pb.into())
     }
+    ///
+    /// Expand directories + globs into the files to read, see [`discover`]
+    ///
+    pub fn discover(&self, files: &[PathBuf]) -> Result<Vec<PathBuf>, DiscoverError> {
+        discover(&self.opts.cwd, files)
+    }
     pub fn read_docs_split(&self, files: &[PathBuf]) -> (Vec<DocResult<Doc>>, Vec<DocResult<Doc>>) {
         self.read_docs(&files).into_iter().partition(|a| match a {
             Ok(doc) => doc.errors.is_empty(),
@@ -60,6 +60,16 @@ impl Display for Cwd {
     }
 }
 
+impl Cwd {
+    ///
+    /// The directory topics was run from, kept relative so that documents
+    /// don't carry the path of whoever printed them
+    ///
+    pub fn project_root() -> Self {
+        Self(PathBuf::from("./"))
+    }
+}
+
 impl Default for Cwd {
     fn default() -> Self {
         Self(current_dir().expect("can access cwd"))
@@ -1,103 +1,660 @@
-use std::collections::HashMap;
-use std::fmt::Display;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
 
-use crate::db_error::{CycleError, DbError, ErrorRef, IntoDbError, SerializedError};
+use crate::db_error::{
+    ConfigRefError, CycleError, CycleHop, DbError, DefinitionSite, DuplicateNameError, IntoDbError,
+    MissingRefError, SerializedError, ShellSyntaxError, UnsetVarError,
+};
 use crate::doc::Doc;
-use crate::doc_src::{DocSource, MdDocSource, MdSrc};
+use crate::doc_src::{DocSource, ItemSrc, MdSrc};
 use crate::html::output_html;
-use crate::items::{marker_ref, name_ref, Item, ItemWrap, LineMarker};
-use crate::output::{output, Output, Outputs};
+use crate::items::{marker_ref, name_ref, Command, Config, Item, ItemWrap, LineMarker};
+use crate::markdown::output_md;
+use crate::output::{output, Outputs};
 use crate::print::OutputKind;
+use crate::shell::analyze;
 
+///
+/// Every item across all loaded documents, along with the graph of references between them.
+///
+/// Everything here borrows from the documents, so it's only available inside [`with_db`]
+///
 #[derive(Debug)]
-pub struct Db {}
+pub struct Db<'a> {
+    pub graph: &'a HashMap<&'a String, Vec<&'a LineMarker<String>>>,
+    pub item_lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
+    pub items: &'a Vec<(ItemSrc<'a>, Vec<Item>)>,
+}
+
+impl<'a> Db<'a> {
+    pub fn get(&self, name: &str) -> Option<&(ItemSrc<'a>, &'a Item)> {
+        self.item_lookup.get(&name.to_string())
+    }
+    ///
+    /// Up to 3 known item names that are 'close' to the given one
+    ///
+    pub fn suggestions(&self, name: &str) -> Vec<String> {
+        suggestions(name, self.item_lookup)
+    }
+    ///
+    /// Every item across all loaded documents, including those defined
+    /// inline within Topics, in the order they were defined
+    ///
+    pub fn all_items(&self) -> Vec<&'a Item> {
+        self.items
+            .iter()
+            .flat_map(|(_, items)| items.iter())
+            .flat_map(with_inline)
+            .collect()
+    }
+}
 
 pub fn try_from_docs(docs: &[Doc], output_kind: &OutputKind) -> anyhow::Result<Outputs> {
-    let mut src_items: Vec<MdSrc> = vec![];
+    with_db(docs, |db, errors| match output_kind {
+        OutputKind::Markdown => Ok(Outputs::Markdown(output_md(
+            db.item_lookup,
+            db.items,
+            errors,
+        ))),
+        OutputKind::Plain => Ok(Outputs::Plain(output(db.items, errors))),
+        OutputKind::Json | OutputKind::Sarif => Ok(Outputs::Json(output(db.items, errors))),
+        OutputKind::Html => Ok(Outputs::Html(output_html(db.item_lookup, db.items, errors))),
+    })
+}
+
+///
+/// Parse + link every item in the given documents, then call `f` with the resulting [`Db`]
+/// and any errors found in the graph (cycles, missing references etc)
+///
+pub fn with_db<T>(docs: &[Doc], f: impl for<'a> FnOnce(&Db<'a>, Vec<DbError<'a>>) -> T) -> T {
+    let mut src_items: Vec<(MdSrc, &Vec<Item>)> = vec![];
 
     for doc in docs {
         if let DocSource::Md(md) = &doc.source {
-            for item in &md.doc_src_items.items {
+            // items were read along with the document, see `MdDocSource::read_items`
+            for (item, items) in md.doc_src_items.items.iter().zip(&md.items) {
                 let next = MdSrc::new(md, item);
-                src_items.push(next);
+                src_items.push((next, items));
             }
         }
     }
 
-    for item in src_items.iter() {
+    // the AST is still needed to render parts of each document later
+    for (item, _) in src_items.iter() {
         item.parse();
     }
 
     let mut graph: HashMap<&'_ String, Vec<&'_ LineMarker<String>>> = HashMap::new();
 
-    let items: Vec<(&'_ MdSrc, Vec<Item>)> =
-        src_items.iter().map(|src| (src, src.as_items())).collect();
+    let mut items: Vec<(ItemSrc, Vec<Item>)> = src_items
+        .iter()
+        .map(|(src, items)| (ItemSrc::Md(src), (*items).clone()))
+        .collect();
+
+    // every other format is parsed into items up front, see `Doc::from_doc_src`
+    for doc in docs {
+        if !doc.items.is_empty() {
+            items.push((ItemSrc::Doc(doc), doc.items.clone()));
+        }
+    }
 
-    let mut item_lookup: HashMap<&'_ String, (&'_ MdSrc, &'_ Item)> = HashMap::new();
+    let mut definitions: HashMap<&'_ String, Vec<(ItemSrc, &'_ Item)>> = HashMap::new();
 
-    for (mdsrc, items) in &items {
-        for item in items {
-            item_lookup.insert(name_ref(item), (mdsrc, item));
+    // items defined inline within a Topic are registered just like top-level ones
+    for (src, items) in &items {
+        for item in items.iter().flat_map(with_inline) {
+            definitions
+                .entry(name_ref(item))
+                .or_insert_with(Vec::new)
+                .push((*src, item));
         }
     }
 
-    for (_mdsrc, items) in &items {
-        for item in items {
+    // when a name is defined more than once, the first definition is used
+    // for lookups, and every definition site is reported as an error below
+    let item_lookup: HashMap<&'_ String, (ItemSrc, &'_ Item)> = definitions
+        .iter()
+        .map(|(name, defs)| (*name, defs[0]))
+        .collect();
+
+    for (_src, items) in &items {
+        for item in items.iter().flat_map(with_inline) {
             let lm = marker_ref(item);
             let entry = graph.entry(&lm.item).or_insert(Vec::new());
-            if let Item::Topic(topic) = item {
-                for named_ref in topic.deps.iter().chain(topic.steps.iter()) {
-                    match named_ref {
-                        ItemWrap::NamedRef(line_marker) => {
-                            entry.push(line_marker);
-                        }
-                        ItemWrap::Item(_) => todo!("inline item"),
+            for named_ref in entries(item) {
+                match named_ref {
+                    ItemWrap::NamedRef(line_marker) => {
+                        entry.push(line_marker);
+                    }
+                    ItemWrap::Item(inline) => {
+                        entry.push(marker_ref(inline));
                     }
                 }
             }
         }
     }
 
-    match output_kind {
-        OutputKind::Markdown => todo!("markdown"),
-        OutputKind::Plain => Ok(Outputs::Plain(output(&graph, &item_lookup, &items))),
-        OutputKind::Json => Ok(Outputs::Json(output(&graph, &item_lookup, &items))),
-        OutputKind::Html => Ok(Outputs::Html(output_html(&graph, &item_lookup, &items))),
+    let mut errors = detect_duplicates(&definitions);
+    errors.extend(detect_cycle(&graph, &item_lookup));
+    errors.extend(detect_missing_refs(&graph, &item_lookup));
+    errors.extend(detect_config_refs(&item_lookup));
+    errors.extend(detect_shell_errors(&item_lookup));
+
+    let db = Db {
+        graph: &graph,
+        item_lookup: &item_lookup,
+        items: &items,
+    };
+    f(&db, errors)
+}
+
+///
+/// Every dependency + step of a Topic, or the steps of a Task Group
+///
+pub(crate) fn entries(item: &Item) -> Vec<&ItemWrap> {
+    match item {
+        Item::Topic(topic) => topic.deps.iter().chain(topic.steps.iter()).collect(),
+        Item::TaskGroup(tg) => tg.steps.iter().collect(),
+        _ => vec![],
+    }
+}
+
+///
+/// An item, followed by every item defined inline within it (at any depth)
+///
+pub(crate) fn with_inline(item: &Item) -> Vec<&Item> {
+    let mut output = vec![item];
+    for entry in entries(item) {
+        if let ItemWrap::Item(inline) = entry {
+            output.extend(with_inline(inline));
+        }
+    }
+    output
+}
+
+///
+/// Find every item name that was defined more than once across all loaded documents.
+///
+/// Without this, which definition 'wins' would depend on the order in which files were given
+///
+pub(crate) fn detect_duplicates<'a>(
+    definitions: &'a HashMap<&'a String, Vec<(ItemSrc<'a>, &'a Item)>>,
+) -> Vec<DbError<'a>> {
+    let mut output: Vec<DbError> = vec![];
+    let mut names = definitions.keys().copied().collect::<Vec<&String>>();
+    names.sort();
+    for name in names {
+        let defs = &definitions[name];
+        if defs.len() < 2 {
+            continue;
+        }
+        let sites = defs
+            .iter()
+            .map(|(src, item)| DefinitionSite {
+                kind: item.kind_name(),
+                line_start: src.line_in_file(marker_ref(item).line_start),
+                input_file: src.input_file().cloned(),
+            })
+            .collect::<Vec<DefinitionSite>>();
+        let (src, item) = defs[0];
+        let duplicate_err = DuplicateNameError::new(name, sites);
+        output.push(duplicate_err.into_db_error(src, item));
+    }
+    output
+}
+
+///
+/// Find every cycle in the graph by walking its strongly connected components.
+///
+/// Each component that loops back on itself is reported exactly once, with the
+/// full ordered path starting from the alphabetically-first member.
+///
+pub(crate) fn detect_cycle<'a>(
+    graph: &'a HashMap<&'a String, Vec<&'a LineMarker<String>>>,
+    lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
+) -> Vec<DbError<'a>> {
+    let mut output: Vec<DbError> = vec![];
+    for component in strongly_connected(graph) {
+        let path = cycle_path(graph, &component);
+        let hops = path
+            .iter()
+            .map(|(from, to)| {
+                let src = lookup.get(from).map(|(src, _)| *src);
+                CycleHop {
+                    from: (*from).clone(),
+                    to: LineMarker::new(
+                        to.item.clone(),
+                        src.and_then(|src| src.line_in_file(to.line_start)),
+                    ),
+                    input_file: src.and_then(|src| src.input_file().cloned()),
+                }
+            })
+            .collect::<Vec<CycleHop>>();
+        if let Some((first, _)) = path.first() {
+            if let Some((src, item)) = lookup.get(first) {
+                let cycle_err = CycleError::new(hops);
+                output.push(cycle_err.into_db_error(*src, item));
+            }
+        }
     }
+    output
 }
 
-fn detect_cycle<'a>(
+///
+/// Find every named reference (in a Topic's deps or steps, or a Task Group's steps)
+/// that does not resolve to a known item.
+///
+/// Each error carries suggestions based on the names that *do* exist, since the
+/// most common cause is a typo or an item that was renamed elsewhere
+///
+pub(crate) fn detect_missing_refs<'a>(
     graph: &'a HashMap<&'a String, Vec<&'a LineMarker<String>>>,
-    lookup: &'a HashMap<&'a String, (&'a MdSrc<'a>, &'a Item)>,
+    lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
 ) -> Vec<DbError<'a>> {
     let mut output: Vec<DbError> = vec![];
-    for (parent_name, list_of_names) in graph {
-        for child_name_marker in list_of_names {
-            let child_name = &child_name_marker.item;
-            if let Some(child_list) = graph.get(child_name) {
-                let found = child_list.iter().find(|n| n.item == **parent_name);
-                if let Some(_child_m) = found {
-                    if let Some((src, item)) = lookup.get(parent_name) {
-                        let cycle_err = CycleError::new(*parent_name, (*child_name_marker).clone());
-                        let db_err = cycle_err.into_db_error(src, item);
-                        output.push(db_err);
+    let mut names = graph.keys().copied().collect::<Vec<&String>>();
+    names.sort();
+    for name in names {
+        let (src, item) = match lookup.get(name) {
+            Some(found) => *found,
+            None => continue,
+        };
+        for marker in graph.get(name).into_iter().flatten() {
+            if lookup.contains_key(&marker.item) {
+                continue;
+            }
+            let missing = MissingRefError {
+                topic: name.clone(),
+                reference: LineMarker::new(
+                    marker.item.clone(),
+                    src.line_in_file(marker.line_start),
+                ),
+                input_file: src.input_file().cloned(),
+                suggestions: suggestions(&marker.item, lookup),
+            };
+            output.push(missing.into_db_error(src, item));
+        }
+    }
+    output
+}
+
+///
+/// Find every Command whose env reads from a Config (or a path within one) that doesn't exist
+///
+pub(crate) fn detect_config_refs<'a>(
+    lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
+) -> Vec<DbError<'a>> {
+    let mut output: Vec<DbError> = vec![];
+    let mut names = lookup.keys().copied().collect::<Vec<&String>>();
+    names.sort();
+    for name in names {
+        let (src, item) = lookup[name];
+        let env = match item {
+            Item::Command(Command { env: Some(env), .. }) => env,
+            _ => continue,
+        };
+        for values_from in &env.values_from {
+            let config = config_lookup(lookup)(&values_from.config);
+            let found = config.and_then(|config| config.values_at(&values_from.path));
+            if found.is_some() {
+                continue;
+            }
+            let suggestions = match config {
+                Some(_) => vec![],
+                None => suggestions(&values_from.config, lookup)
+                    .into_iter()
+                    .filter(|name| config_lookup(lookup)(name).is_some())
+                    .collect(),
+            };
+            let config_err = ConfigRefError {
+                command: name.clone(),
+                config: values_from.config.clone(),
+                path: values_from.path.clone(),
+                config_exists: config.is_some(),
+                line_start: src.line_in_file(marker_ref(item).line_start),
+                input_file: src.input_file().cloned(),
+                suggestions,
+            };
+            output.push(config_err.into_db_error(src, item));
+        }
+    }
+    output
+}
+
+///
+/// Statically check the script of every Command + Dependency Check, see [`analyze`].
+///
+/// Syntax errors point at the offending line within the markdown file. A Command's
+/// variables may come from its Env, whereas a Dependency Check has nothing to supply them.
+/// Variables set in the environment this runs in are never reported
+///
+pub(crate) fn detect_shell_errors<'a>(
+    lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
+) -> Vec<DbError<'a>> {
+    let mut output: Vec<DbError> = vec![];
+    let mut names = lookup.keys().copied().collect::<Vec<&String>>();
+    names.sort();
+    for name in names {
+        let (src, item) = lookup[name];
+        let (script, block_line, supplied) = match item {
+            Item::Command(cmd) => (
+                &cmd.command,
+                cmd.command_line,
+                cmd.env_values(config_lookup(lookup)),
+            ),
+            Item::DependencyCheck(dc) => (&dc.verify, dc.verify_line, HashMap::new()),
+            _ => continue,
+        };
+        // scripts from markdown can be traced back to their code block,
+        // for everything else the best we can do is the item itself
+        let script_line = |line: u32| match block_line {
+            Some(fence_line) => src.line_in_file(Some(fence_line + line)),
+            None => src.line_in_file(marker_ref(item).line_start),
+        };
+        let analysis = analyze(script);
+        if let Some(syntax_error) = &analysis.syntax_error {
+            let shell_err = ShellSyntaxError {
+                name: name.clone(),
+                kind: item.kind_name(),
+                message: syntax_error.message.clone(),
+                line_start: script_line(syntax_error.line),
+                input_file: src.input_file().cloned(),
+            };
+            output.push(shell_err.into_db_error(src, item));
+        }
+        // the environment of whoever runs this is not consulted, so results are the same on every machine
+        let vars = analysis.unset_vars(|var| supplied.contains_key(var));
+        if !vars.is_empty() {
+            let unset_err = UnsetVarError {
+                name: name.clone(),
+                kind: item.kind_name(),
+                vars,
+                line_start: script_line(1),
+                input_file: src.input_file().cloned(),
+            };
+            output.push(unset_err.into_db_error(src, item));
+        }
+    }
+    output
+}
+
+///
+/// Split the problems found in the graph into errors, which stop commands from
+/// continuing, and warnings which are only reported, see [`SerializedError::is_warning`]
+///
+pub fn split_warnings(errors: Vec<DbError>) -> (Vec<SerializedError>, Vec<SerializedError>) {
+    errors
+        .into_iter()
+        .map(SerializedError::from)
+        .partition(|err| !err.is_warning())
+}
+
+///
+/// Find a Config by name, for use with [`Command::interpolated`] + [`Command::env_values`]
+///
+pub fn config_lookup<'a>(
+    lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
+) -> impl Fn(&str) -> Option<&'a Config> {
+    move |name| match lookup.get(&name.to_string()) {
+        Some((_, Item::Config(config))) => Some(config),
+        _ => None,
+    }
+}
+
+///
+/// Up to 3 known item names that are 'close' to the given one
+///
+fn suggestions(name: &str, lookup: &HashMap<&String, (ItemSrc, &Item)>) -> Vec<String> {
+    let name = name.to_lowercase();
+    // a fifth of the name allows for typos, anything looser starts to suggest
+    // unrelated items that share a prefix, eg: `install helm` -> `install yarn`
+    let max_distance = std::cmp::max(1, name.chars().count() / 5);
+    let mut candidates = lookup
+        .keys()
+        .filter_map(|key| {
+            let distance = edit_distance(&name, &key.to_lowercase());
+            if distance <= max_distance {
+                Some((distance, (*key).clone()))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<(usize, String)>>();
+    candidates.sort();
+    candidates.into_iter().take(3).map(|(_, key)| key).collect()
+}
+
+///
+/// Levenshtein distance between 2 strings
+///
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars = b.chars().collect::<Vec<char>>();
+    let mut prev = (0..=b_chars.len()).collect::<Vec<usize>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let cost = if a_char == *b_char { 0 } else { 1 };
+            let next = (prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1);
+            current.push(next);
+        }
+        prev = current;
+    }
+    prev[b_chars.len()]
+}
+
+///
+/// Indexes of the names that contain every character of `query` in order (ignoring case),
+/// best matches first. Characters that sit next to each other score better than those
+/// spread across the name, eg: `bld` matches both `Build` and `Bundle deps`, but `Build` comes first.
+///
+/// An empty query matches everything, in the original order
+///
+pub(crate) fn fuzzy_matches(query: &str, names: &[String]) -> Vec<usize> {
+    let query = query.trim().to_lowercase().chars().collect::<Vec<char>>();
+    let mut matches = names
+        .iter()
+        .enumerate()
+        .filter_map(|(index, name)| {
+            fuzzy_score(&query, &name.to_lowercase()).map(|score| (score, index))
+        })
+        .collect::<Vec<(usize, usize)>>();
+    matches.sort_unstable();
+    matches.into_iter().map(|(_, index)| index).collect()
+}
+
+///
+/// The number of skipped characters between the first + last matched ones, or `None` when
+/// `name` does not contain every character of `query`
+///
+fn fuzzy_score(query: &[char], name: &str) -> Option<usize> {
+    let mut remaining = query.iter().peekable();
+    let mut first = None;
+    let mut gaps = 0;
+    for (index, ch) in name.chars().enumerate() {
+        match remaining.peek() {
+            None => break,
+            Some(wanted) if **wanted == ch => {
+                remaining.next();
+                first.get_or_insert(index);
+            }
+            Some(_) if first.is_some() => gaps += 1,
+            Some(_) => {}
+        }
+    }
+    match remaining.peek() {
+        None => Some(gaps),
+        Some(_) => None,
+    }
+}
+
+///
+/// Tarjan's algorithm - returns every strongly connected component in the graph.
+///
+/// Keys are visited in sorted order so that the output is stable between runs
+///
+fn strongly_connected<'a>(
+    graph: &'a HashMap<&'a String, Vec<&'a LineMarker<String>>>,
+) -> Vec<Vec<&'a String>> {
+    struct Tarjan<'a> {
+        graph: &'a HashMap<&'a String, Vec<&'a LineMarker<String>>>,
+        index: usize,
+        indices: HashMap<&'a String, usize>,
+        low_links: HashMap<&'a String, usize>,
+        stack: Vec<&'a String>,
+        on_stack: HashSet<&'a String>,
+        components: Vec<Vec<&'a String>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        fn visit(&mut self, name: &'a String) {
+            self.indices.insert(name, self.index);
+            self.low_links.insert(name, self.index);
+            self.index += 1;
+            self.stack.push(name);
+            self.on_stack.insert(name);
+
+            let graph = self.graph;
+            for marker in graph.get(name).into_iter().flatten() {
+                let marker: &'a LineMarker<String> = marker;
+                let next = &marker.item;
+                if !self.indices.contains_key(next) {
+                    self.visit(next);
+                    let low = self.low_links[name].min(self.low_links[next]);
+                    self.low_links.insert(name, low);
+                } else if self.on_stack.contains(next) {
+                    let low = self.low_links[name].min(self.indices[next]);
+                    self.low_links.insert(name, low);
+                }
+            }
+
+            if self.low_links[name] == self.indices[name] {
+                let mut component = vec![];
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member);
+                    if member == name {
+                        break;
                     }
                 }
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        graph,
+        index: 0,
+        indices: HashMap::new(),
+        low_links: HashMap::new(),
+        stack: vec![],
+        on_stack: HashSet::new(),
+        components: vec![],
+    };
+
+    let mut names = graph.keys().copied().collect::<Vec<&String>>();
+    names.sort();
+    for name in names {
+        if !tarjan.indices.contains_key(name) {
+            tarjan.visit(name);
+        }
+    }
+    tarjan.components
+}
+
+///
+/// Every edge that's part of a cycle, as `(from, to)` names
+///
+pub(crate) fn cycle_edges<'a>(
+    graph: &'a HashMap<&'a String, Vec<&'a LineMarker<String>>>,
+) -> HashSet<(&'a String, &'a String)> {
+    let mut output = HashSet::new();
+    for component in strongly_connected(graph) {
+        let members = component.iter().copied().collect::<HashSet<&String>>();
+        for from in component {
+            for marker in graph.get(from).into_iter().flatten() {
+                let marker: &'a LineMarker<String> = marker;
+                if members.contains(&marker.item) {
+                    output.insert((from, &marker.item));
+                }
             }
         }
     }
     output
 }
 
+///
+/// Walk a single strongly connected component to produce the ordered
+/// list of edges that lead back to where it started.
+///
+/// An empty Vec means the component does not loop (eg: a single node without a self-reference)
+///
+fn cycle_path<'a>(
+    graph: &'a HashMap<&'a String, Vec<&'a LineMarker<String>>>,
+    component: &[&'a String],
+) -> Vec<(&'a String, &'a LineMarker<String>)> {
+    fn walk<'a>(
+        graph: &'a HashMap<&'a String, Vec<&'a LineMarker<String>>>,
+        members: &HashSet<&'a String>,
+        start: &'a String,
+        current: &'a String,
+        visited: &mut HashSet<&'a String>,
+        path: &mut Vec<(&'a String, &'a LineMarker<String>)>,
+    ) -> bool {
+        visited.insert(current);
+        for marker in graph.get(current).into_iter().flatten() {
+            let marker: &'a LineMarker<String> = marker;
+            let next = &marker.item;
+            if !members.contains(next) {
+                continue;
+            }
+            path.push((current, marker));
+            if next == start {
+                return true;
+            }
+            if !visited.contains(next) && walk(graph, members, start, next, visited, path) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    let members = component.iter().copied().collect::<HashSet<&String>>();
+    let mut path = vec![];
+    if let Some(start) = component.iter().copied().min() {
+        if !walk(
+            graph,
+            &members,
+            start,
+            start,
+            &mut HashSet::new(),
+            &mut path,
+        ) {
+            path.clear();
+        }
+    }
+    path
+}
+
 #[cfg(test)]
 mod test {
     use std::path::PathBuf;
 
     use crate::context::Context;
+    use crate::db_error::{ErrCode, SerializedError};
+    use crate::output::Output;
 
     use super::*;
 
+    fn json_output(paths: &[&str]) -> Output {
+        let ctx = Context::default();
+        let paths = paths.iter().map(PathBuf::from).collect::<Vec<PathBuf>>();
+        let f = ctx.read_docs_unwrapped(&paths);
+        match try_from_docs(&f, &OutputKind::Json) {
+            Ok(Outputs::Json(output)) => output,
+            _ => unreachable!(),
+        }
+    }
+
     #[test]
     fn test_doc_from_src() {
         let ctx = Context::default();
@@ -109,4 +666,357 @@ mod test {
         let db = try_from_docs(&f, &OutputKind::Json);
         dbg!(db);
     }
+
+    #[test]
+    fn test_detect_long_cycle() {
+        let output = json_output(&["../fixtures/cycle/topics.md"]);
+        assert_eq!(output.errors.len(), 1);
+        let cycle = match output.errors.get(0) {
+            Some(SerializedError::Cycle(cycle)) => cycle,
+            _ => unreachable!(),
+        };
+        let hops = cycle
+            .path
+            .iter()
+            .map(|hop| (hop.from.as_str(), hop.to.item.as_str(), hop.to.line_start))
+            .collect::<Vec<(&str, &str, Option<u32>)>>();
+        assert_eq!(
+            hops,
+            vec![
+                ("Build the app", "Run the app", Some(13)),
+                ("Run the app", "Install dependencies", Some(21)),
+                ("Install dependencies", "Build the app", Some(5)),
+            ]
+        );
+        assert_eq!(
+            cycle.path[0].input_file,
+            Some(PathBuf::from("../fixtures/cycle/topics.md"))
+        );
+    }
+
+    #[test]
+    fn test_detect_missing_refs() {
+        let output = json_output(&["../fixtures/missing/topics.md"]);
+        let missing = output
+            .errors
+            .iter()
+            .filter_map(|err| match err {
+                SerializedError::MissingRef(missing) => Some((
+                    missing.reference.item.as_str(),
+                    missing.reference.line_start,
+                    missing.suggestions.clone(),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<(&str, Option<u32>, Vec<String>)>>();
+        assert_eq!(
+            missing,
+            vec![
+                (
+                    "install dokcer",
+                    Some(5),
+                    vec![String::from("install docker")]
+                ),
+                ("install helm", Some(6), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_refs_from_print_json() -> anyhow::Result<()> {
+        let ctx = Context::default();
+        let output = json_output(&["../fixtures/missing/topics.md"]);
+        let printed = serde_json::to_string_pretty(&output)?;
+        // the output of `topics print -p json`, read back in as a document of its own
+        let doc = Doc::from_content("printed.json", &printed, &ctx)?;
+        let output = match try_from_docs(&[doc], &OutputKind::Json) {
+            Ok(Outputs::Json(output)) => output,
+            _ => unreachable!(),
+        };
+        let line = output
+            .errors
+            .iter()
+            .find_map(|err| match err {
+                SerializedError::MissingRef(missing)
+                    if missing.reference.item == "install dokcer" =>
+                {
+                    missing.reference.line_start
+                }
+                _ => None,
+            })
+            .expect("a line within printed.json");
+        let text = printed.lines().nth(line as usize - 1).unwrap_or_default();
+        assert!(text.contains("\"install dokcer\""), "line {}: {}", line, text);
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_duplicates() {
+        let output = json_output(&[
+            "../fixtures/duplicate/team_a.md",
+            "../fixtures/duplicate/team_b.md",
+        ]);
+        assert_eq!(output.errors.len(), 1);
+        let duplicate = match output.errors.get(0) {
+            Some(SerializedError::DuplicateName(duplicate)) => duplicate,
+            _ => unreachable!(),
+        };
+        assert_eq!(duplicate.name, "install node");
+        let sites = duplicate
+            .definitions
+            .iter()
+            .map(|site| (site.input_file.clone(), site.line_start))
+            .collect::<Vec<(Option<PathBuf>, Option<u32>)>>();
+        assert_eq!(
+            sites,
+            vec![
+                (
+                    Some(PathBuf::from("../fixtures/duplicate/team_a.md")),
+                    Some(1)
+                ),
+                (
+                    Some(PathBuf::from("../fixtures/duplicate/team_b.md")),
+                    Some(9)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_snippet() {
+        use crate::print::snippet::{render, Label};
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[
+            PathBuf::from("../fixtures/duplicate/team_a.md"),
+            PathBuf::from("../fixtures/duplicate/team_b.md"),
+        ]);
+        let labels = with_db(&f, |_db, errors| {
+            let err = SerializedError::from(errors.into_iter().next().expect("1 error"));
+            err.labels()
+                .into_iter()
+                .map(|(file, line, message)| Label {
+                    file: file.cloned(),
+                    line: line.expect("every definition has a line"),
+                    column: None,
+                    message,
+                    context: None,
+                })
+                .collect::<Vec<Label>>()
+        });
+        let snippet = render(&labels, |pb| std::fs::read_to_string(pb?).ok(), false);
+        let expected = r#"  --> ../fixtures/duplicate/team_a.md:1
+  |
+1 | # Dependency Check: install node
+  | ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ Dependency Check defined here first
+  |
+  ::: ../fixtures/duplicate/team_b.md:9
+  |
+9 | # Dependency Check: install node
+  | -------------------------------- Dependency Check defined again here
+  |
+"#;
+        assert_eq!(snippet.as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn test_yaml_items_join_graph() {
+        let output = json_output(&[
+            "../fixtures/graph/topics.yaml",
+            "../fixtures/graph/deps.yaml",
+            "../fixtures/graph/commands.yaml",
+        ]);
+        assert_eq!(output.items.len(), 10);
+        assert_eq!(output.errors.len(), 1);
+        match output.errors.get(0) {
+            Some(SerializedError::MissingRef(missing)) => {
+                assert_eq!(missing.topic, "Kubernetes setup");
+                assert_eq!(missing.reference.item, "install helm");
+                assert_eq!(missing.reference.line_start, Some(18));
+                assert_eq!(
+                    missing.input_file,
+                    Some(PathBuf::from("../fixtures/graph/topics.yaml"))
+                );
+            }
+            _ => unreachable!(),
+        };
+    }
+
+    #[test]
+    fn test_config_interpolation() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[
+            PathBuf::from("../fixtures/md/config.md"),
+            PathBuf::from("../fixtures/md/commands.md"),
+        ]);
+        let (errors, command) = with_db(&f, |db, errors| {
+            let command = match db.get("Cargo Test") {
+                Some((_, Item::Command(cmd))) => cmd.interpolated(config_lookup(db.item_lookup)),
+                _ => unreachable!(),
+            };
+            (errors.len(), command)
+        });
+        assert_eq!(errors, 0);
+        assert_eq!(
+            command,
+            "docker build -t nbs-frontend ./containers/www/client\ncargo test"
+        );
+    }
+
+    #[test]
+    fn test_detect_config_refs() {
+        let output = json_output(&["../fixtures/config/broken.md"]);
+        let errors = output
+            .errors
+            .iter()
+            .filter_map(|err| match err {
+                SerializedError::ConfigRef(config_err) => Some((
+                    config_err.config.as_str(),
+                    config_err.path.as_str(),
+                    config_err.config_exists,
+                    config_err.suggestions.clone(),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<(&str, &str, bool, Vec<String>)>>();
+        assert_eq!(
+            errors,
+            vec![
+                ("global-vars", "images.clint", true, vec![]),
+                (
+                    "globl-vars",
+                    "images.graph",
+                    false,
+                    vec![String::from("global-vars")]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_shell_errors() {
+        let output = json_output(&["../fixtures/shell/scripts.md"]);
+        assert_eq!(output.errors.len(), 2);
+        match output.errors.get(0) {
+            Some(SerializedError::UnsetVar(unset)) => {
+                assert_eq!(unset.name, "build the client");
+                assert_eq!(unset.vars, vec![String::from("CONTEXT_DIR")]);
+                assert_eq!(unset.line_start, Some(8));
+            }
+            _ => unreachable!(),
+        };
+        match output.errors.get(1) {
+            Some(SerializedError::ShellSyntax(syntax)) => {
+                assert_eq!(syntax.name, "docker running");
+                assert_eq!(syntax.line_start, Some(18));
+                assert_eq!(
+                    syntax.input_file,
+                    Some(PathBuf::from("../fixtures/shell/scripts.md"))
+                );
+            }
+            _ => unreachable!(),
+        };
+    }
+
+    #[test]
+    fn test_unset_vars_are_warnings() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/shell/scripts.md")]);
+        let (errors, warnings) = with_db(&f, |_db, errors| split_warnings(errors));
+        let codes =
+            |errors: &[SerializedError]| errors.iter().map(|err| err.code()).collect::<Vec<&str>>();
+        assert_eq!(codes(&errors), vec![ShellSyntaxError::CODE]);
+        assert_eq!(codes(&warnings), vec![UnsetVarError::CODE]);
+    }
+
+    #[test]
+    fn test_inline_items() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/md/inline.md")]);
+        with_db(&f, |db, errors| {
+            assert_eq!(errors.len(), 0);
+            let refs = db.graph[&String::from("Build the app")]
+                .iter()
+                .map(|lm| (lm.item.as_str(), lm.line_start))
+                .collect::<Vec<(&str, Option<u32>)>>();
+            assert_eq!(
+                refs,
+                vec![
+                    ("yarn installed", Some(5)),
+                    ("Clone the repo", Some(11)),
+                    ("install deps", Some(16)),
+                    ("run the build", Some(22)),
+                ]
+            );
+            match db.get("install deps") {
+                Some((src, Item::Command(cmd))) => {
+                    assert_eq!(cmd.command, "yarn install");
+                    assert_eq!(src.line_in_file(cmd.name.line_start), Some(16));
+                }
+                _ => unreachable!(),
+            };
+            match db.get("yarn installed") {
+                Some((_, Item::DependencyCheck(dc))) => assert_eq!(dc.verify, "yarn -v"),
+                _ => unreachable!(),
+            };
+            match db.get("Clone the repo") {
+                Some((_, Item::Instruction(inst))) => assert_eq!(
+                    inst.instruction.as_deref(),
+                    Some("Ask for access in the #frontend channel")
+                ),
+                _ => unreachable!(),
+            };
+        });
+    }
+
+    #[test]
+    fn test_md_task_group_and_checks() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/md/checks.md")]);
+        with_db(&f, |db, errors| {
+            assert_eq!(errors.len(), 0);
+            let refs = db.graph[&String::from("Local setup")]
+                .iter()
+                .map(|lm| lm.item.as_str())
+                .collect::<Vec<&str>>();
+            assert_eq!(refs, vec![".npmrc exists", "local domains", "print ready"]);
+            match db.get(".npmrc exists") {
+                Some((_, Item::FileExistsCheck(fec))) => {
+                    assert_eq!(fec.path, PathBuf::from(".npmrc"))
+                }
+                _ => unreachable!(),
+            };
+            match db.get("local domains") {
+                Some((src, Item::HostEntriesCheck(hec))) => {
+                    let domains = hec
+                        .hosts
+                        .iter()
+                        .map(|host| host.domain.as_str())
+                        .collect::<Vec<&str>>();
+                    assert_eq!(domains, vec!["app.local", "api.local"]);
+                    assert_eq!(src.line_in_file(hec.name.line_start), Some(27));
+                }
+                _ => unreachable!(),
+            };
+        });
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("install docker", "install docker"), 0);
+        assert_eq!(edit_distance("install dokcer", "install docker"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_fuzzy_matches() {
+        let names = vec![
+            String::from("Bundle deps"),
+            String::from("Build"),
+            String::from("Run tests"),
+        ];
+        assert_eq!(fuzzy_matches("bld", &names), vec![1, 0]);
+        assert_eq!(fuzzy_matches("BUILD", &names), vec![1]);
+        assert_eq!(fuzzy_matches("", &names), vec![0, 1, 2]);
+        assert_eq!(fuzzy_matches("xyz", &names), Vec::<usize>::new());
+    }
 }
@@ -1,8 +1,8 @@
-use crate::doc_src::{MdDocSource, MdSrc};
+use crate::doc_src::ItemSrc;
 use crate::items::{Item, LineMarker};
 use std::fmt::{self, Debug, Display, Formatter};
 use std::ops::Deref;
-use std::str::FromStr;
+use std::path::PathBuf;
 use typescript_definitions::TypeScriptify;
 
 ///
@@ -13,16 +13,194 @@ use typescript_definitions::TypeScriptify;
 pub enum DbError<'a> {
     #[error("{}", .0)]
     Cycle(ErrorRef<'a, CycleError>),
+    #[error("{}", .0)]
+    MissingRef(ErrorRef<'a, MissingRefError>),
+    #[error("{}", .0)]
+    DuplicateName(ErrorRef<'a, DuplicateNameError>),
+    #[error("{}", .0)]
+    ConfigRef(ErrorRef<'a, ConfigRefError>),
+    #[error("{}", .0)]
+    ShellSyntax(ErrorRef<'a, ShellSyntaxError>),
+    #[error("{}", .0)]
+    UnsetVar(ErrorRef<'a, UnsetVarError>),
 }
 
 #[derive(Debug, serde::Serialize, TypeScriptify)]
 #[serde(tag = "kind", content = "content")]
 pub enum SerializedError {
     Cycle(CycleError),
+    MissingRef(MissingRefError),
+    DuplicateName(DuplicateNameError),
+    ConfigRef(ConfigRefError),
+    ShellSyntax(ShellSyntaxError),
+    UnsetVar(UnsetVarError),
+}
+
+impl From<DbError<'_>> for SerializedError {
+    fn from(db_err: DbError<'_>) -> Self {
+        match db_err {
+            DbError::Cycle(ErrorRef { inner, .. }) => SerializedError::Cycle(inner),
+            DbError::MissingRef(ErrorRef { inner, .. }) => SerializedError::MissingRef(inner),
+            DbError::DuplicateName(ErrorRef { inner, .. }) => {
+                SerializedError::DuplicateName(inner)
+            }
+            DbError::ConfigRef(ErrorRef { inner, .. }) => SerializedError::ConfigRef(inner),
+            DbError::ShellSyntax(ErrorRef { inner, .. }) => SerializedError::ShellSyntax(inner),
+            DbError::UnsetVar(ErrorRef { inner, .. }) => SerializedError::UnsetVar(inner),
+        }
+    }
+}
+
+impl Display for SerializedError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SerializedError::Cycle(err) => write!(f, "error[{}] {}", CycleError::CODE, err),
+            SerializedError::MissingRef(err) => {
+                write!(f, "error[{}] {}", MissingRefError::CODE, err)
+            }
+            SerializedError::DuplicateName(err) => {
+                write!(f, "error[{}] {}", DuplicateNameError::CODE, err)
+            }
+            SerializedError::ConfigRef(err) => {
+                write!(f, "error[{}] {}", ConfigRefError::CODE, err)
+            }
+            SerializedError::ShellSyntax(err) => {
+                write!(f, "error[{}] {}", ShellSyntaxError::CODE, err)
+            }
+            SerializedError::UnsetVar(err) => {
+                write!(f, "warning[{}] {}", UnsetVarError::CODE, err)
+            }
+        }
+    }
+}
+
+impl SerializedError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            SerializedError::Cycle(_) => CycleError::CODE,
+            SerializedError::MissingRef(_) => MissingRefError::CODE,
+            SerializedError::DuplicateName(_) => DuplicateNameError::CODE,
+            SerializedError::ConfigRef(_) => ConfigRefError::CODE,
+            SerializedError::ShellSyntax(_) => ShellSyntaxError::CODE,
+            SerializedError::UnsetVar(_) => UnsetVarError::CODE,
+        }
+    }
+    ///
+    /// Warnings are reported, but never stop a command. A variable that nothing in the
+    /// documents supplies may still be set by whoever runs the script
+    ///
+    pub fn is_warning(&self) -> bool {
+        matches!(self, SerializedError::UnsetVar(_))
+    }
+    ///
+    /// The error without its `error[CODE]` prefix
+    ///
+    pub fn message(&self) -> String {
+        match self {
+            SerializedError::Cycle(err) => err.to_string(),
+            SerializedError::MissingRef(err) => err.to_string(),
+            SerializedError::DuplicateName(err) => err.to_string(),
+            SerializedError::ConfigRef(err) => err.to_string(),
+            SerializedError::ShellSyntax(err) => err.to_string(),
+            SerializedError::UnsetVar(err) => err.to_string(),
+        }
+    }
+    ///
+    /// Every `(file, line)` that the error points at, a cycle points at each of its hops
+    /// and a duplicate name at each of its definitions
+    ///
+    pub fn sites(&self) -> Vec<(Option<&PathBuf>, Option<u32>)> {
+        self.labels()
+            .into_iter()
+            .map(|(input_file, line, _)| (input_file, line))
+            .collect()
+    }
+    ///
+    /// The same as [`SerializedError::sites`], along with a short message for each one
+    ///
+    pub fn labels(&self) -> Vec<(Option<&PathBuf>, Option<u32>, String)> {
+        match self {
+            SerializedError::Cycle(err) => err
+                .path
+                .iter()
+                .map(|hop| {
+                    let msg = format!("`{}` refers to `{}`", hop.from, hop.to.item);
+                    (hop.input_file.as_ref(), hop.to.line_start, msg)
+                })
+                .collect(),
+            SerializedError::MissingRef(err) => {
+                let msg = format!("`{}` is not defined anywhere", err.reference.item);
+                vec![(err.input_file.as_ref(), err.reference.line_start, msg)]
+            }
+            SerializedError::DuplicateName(err) => err
+                .definitions
+                .iter()
+                .enumerate()
+                .map(|(index, site)| {
+                    let msg = if index == 0 {
+                        format!("{} defined here first", site.kind)
+                    } else {
+                        format!("{} defined again here", site.kind)
+                    };
+                    (site.input_file.as_ref(), site.line_start, msg)
+                })
+                .collect(),
+            SerializedError::ConfigRef(err) => {
+                let msg = if err.config_exists {
+                    format!("`{}` is not in Config `{}`", err.path, err.config)
+                } else {
+                    format!("there is no Config named `{}`", err.config)
+                };
+                vec![(err.input_file.as_ref(), err.line_start, msg)]
+            }
+            SerializedError::ShellSyntax(err) => {
+                vec![(err.input_file.as_ref(), err.line_start, err.message.clone())]
+            }
+            SerializedError::UnsetVar(err) => {
+                let vars = err
+                    .vars
+                    .iter()
+                    .map(|var| format!("`${}`", var))
+                    .collect::<Vec<String>>();
+                let msg = format!("nothing supplies {}", vars.join(", "));
+                vec![(err.input_file.as_ref(), err.line_start, msg)]
+            }
+        }
+    }
+    ///
+    /// A suggestion for how to fix the error, when there is one
+    ///
+    pub fn help(&self) -> Option<String> {
+        let quoted = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<String>>()
+        };
+        match self {
+            SerializedError::MissingRef(err) => match err.suggestions.as_slice() {
+                [] => None,
+                [only] => Some(format!("did you mean `{}`?", only)),
+                many => Some(format!("did you mean one of {}?", quoted(many).join(", "))),
+            },
+            SerializedError::ConfigRef(err) if !err.suggestions.is_empty() => Some(format!(
+                "did you mean {}?",
+                quoted(&err.suggestions).join(" or ")
+            )),
+            SerializedError::UnsetVar(err) => err.vars.first().map(|first| {
+                format!(
+                    "add {} to an Env, or give a default, eg: `${{{}:-value}}`",
+                    if err.vars.len() == 1 { "it" } else { "them" },
+                    first
+                )
+            }),
+            _ => None,
+        }
+    }
 }
 
 pub trait IntoDbError<'a> {
-    fn into_db_error(self, src: &'a MdSrc<'a>, item: &'a Item) -> DbError<'a>;
+    fn into_db_error(self, src: ItemSrc<'a>, item: &'a Item) -> DbError<'a>;
 }
 
 #[derive(Debug)]
@@ -32,7 +210,7 @@ where
 {
     pub inner: T,
     pub item: &'a Item,
-    pub src: &'a MdSrc<'a>,
+    pub src: ItemSrc<'a>,
 }
 
 impl<T: Debug> Deref for ErrorRef<'_, T> {
@@ -52,7 +230,7 @@ where
     Err: Debug + ErrCode + Display,
 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let input = self.src.md_doc_src.input_file.as_ref();
+        let input = self.src.input_file();
         let _ = write!(
             f,
             "error[{}] {}",
@@ -70,44 +248,355 @@ where
 
 #[derive(Debug, serde::Serialize, TypeScriptify)]
 pub struct CycleError {
+    pub path: Vec<CycleHop>,
+}
+
+///
+/// A single edge within a cycle, `from` refers to `to` on the line
+/// recorded in the [`LineMarker`]
+///
+#[derive(Debug, Clone, serde::Serialize, TypeScriptify)]
+pub struct CycleHop {
     pub from: String,
     pub to: LineMarker<String>,
+    pub input_file: Option<PathBuf>,
 }
 
 impl CycleError {
-    pub fn new(from: impl Into<String>, to: LineMarker<String>) -> Self {
-        Self {
-            from: from.into(),
-            to,
-        }
+    pub fn new(path: Vec<CycleHop>) -> Self {
+        Self { path }
     }
 }
 
 impl ErrCode for CycleError {
-    const CODE: &'static str = "001";
+    const CODE: &'static str = "E001";
 }
 
 impl Display for CycleError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let names = self
+            .path
+            .iter()
+            .map(|hop| format!("`{}`", hop.from))
+            .chain(self.path.first().map(|hop| format!("`{}`", hop.from)))
+            .collect::<Vec<String>>();
+
+        let _ = write!(f, "Infinite loop detected {} -> ∞", names.join(" -> "));
+
+        for hop in &self.path {
+            let _ = writeln!(f);
+            let _ = write!(f, "    `{}` -> `{}`", hop.from, hop.to.item);
+            match (&hop.input_file, hop.to.line_start) {
+                (Some(pb), Some(line)) => {
+                    let _ = write!(f, " at {}:{}", pb.display(), line);
+                }
+                (None, Some(line)) => {
+                    let _ = write!(f, " on line {}", line);
+                }
+                _ => {}
+            };
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> IntoDbError<'a> for CycleError {
+    fn into_db_error(self, src: ItemSrc<'a>, item: &'a Item) -> DbError<'a> {
+        DbError::Cycle(ErrorRef {
+            inner: self,
+            item,
+            src,
+        })
+    }
+}
+
+///
+/// A Topic or Task Group refers to an item (in its deps or steps) by a name that doesn't exist
+///
+#[derive(Debug, serde::Serialize, TypeScriptify)]
+pub struct MissingRefError {
+    pub topic: String,
+    pub reference: LineMarker<String>,
+    pub input_file: Option<PathBuf>,
+    pub suggestions: Vec<String>,
+}
+
+impl ErrCode for MissingRefError {
+    const CODE: &'static str = "E002";
+}
+
+impl Display for MissingRefError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let _ = write!(
             f,
-            "Infinite loop detected `{}` -> `{}` -> `{}` -> ∞",
-            self.from, self.to.item, self.from
+            "`{}` refers to `{}`, but no item with that name exists",
+            self.topic, self.reference.item
         );
+        if let Some(line) = self.reference.line_start {
+            let _ = writeln!(f);
+            let _ = write!(f, "    check line {}", line);
+        }
+        match self.suggestions.as_slice() {
+            [] => {}
+            [only] => {
+                let _ = writeln!(f);
+                let _ = write!(f, "    did you mean `{}`?", only);
+            }
+            many => {
+                let _ = writeln!(f);
+                let names = many
+                    .iter()
+                    .map(|name| format!("`{}`", name))
+                    .collect::<Vec<String>>();
+                let _ = write!(f, "    did you mean one of {}?", names.join(", "));
+            }
+        };
+        Ok(())
+    }
+}
 
-        let _ = writeln!(f);
+impl<'a> IntoDbError<'a> for MissingRefError {
+    fn into_db_error(self, src: ItemSrc<'a>, item: &'a Item) -> DbError<'a> {
+        DbError::MissingRef(ErrorRef {
+            inner: self,
+            item,
+            src,
+        })
+    }
+}
+
+///
+/// The same item name was defined in more than 1 place
+///
+#[derive(Debug, serde::Serialize, TypeScriptify)]
+pub struct DuplicateNameError {
+    pub name: String,
+    pub definitions: Vec<DefinitionSite>,
+}
 
-        write!(
+///
+/// Where an item was defined, used to point at every conflicting definition
+///
+#[derive(Debug, Clone, serde::Serialize, TypeScriptify)]
+pub struct DefinitionSite {
+    pub kind: String,
+    pub line_start: Option<u32>,
+    pub input_file: Option<PathBuf>,
+}
+
+impl DuplicateNameError {
+    pub fn new(name: impl Into<String>, definitions: Vec<DefinitionSite>) -> Self {
+        Self {
+            name: name.into(),
+            definitions,
+        }
+    }
+}
+
+impl ErrCode for DuplicateNameError {
+    const CODE: &'static str = "E003";
+}
+
+impl Display for DuplicateNameError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let _ = write!(
             f,
-            "    check line {}",
-            self.to.line_start.expect("cannot be absent")
-        )
+            "`{}` is defined {} times, names must be unique across all documents",
+            self.name,
+            self.definitions.len()
+        );
+        for site in &self.definitions {
+            let _ = writeln!(f);
+            let _ = write!(f, "    {} defined", site.kind);
+            match (&site.input_file, site.line_start) {
+                (Some(pb), Some(line)) => {
+                    let _ = write!(f, " at {}:{}", pb.display(), line);
+                }
+                (Some(pb), None) => {
+                    let _ = write!(f, " in {}", pb.display());
+                }
+                (None, Some(line)) => {
+                    let _ = write!(f, " on line {}", line);
+                }
+                (None, None) => {}
+            };
+        }
+        Ok(())
     }
 }
 
-impl<'a> IntoDbError<'a> for CycleError {
-    fn into_db_error(self, src: &'a MdSrc<'a>, item: &'a Item) -> DbError<'a> {
-        DbError::Cycle(ErrorRef {
+impl<'a> IntoDbError<'a> for DuplicateNameError {
+    fn into_db_error(self, src: ItemSrc<'a>, item: &'a Item) -> DbError<'a> {
+        DbError::DuplicateName(ErrorRef {
+            inner: self,
+            item,
+            src,
+        })
+    }
+}
+
+///
+/// A Command's env reads values from a Config that doesn't exist,
+/// or from a path that the Config doesn't contain
+///
+#[derive(Debug, serde::Serialize, TypeScriptify)]
+pub struct ConfigRefError {
+    pub command: String,
+    pub config: String,
+    pub path: String,
+    pub config_exists: bool,
+    pub line_start: Option<u32>,
+    pub input_file: Option<PathBuf>,
+    pub suggestions: Vec<String>,
+}
+
+impl ErrCode for ConfigRefError {
+    const CODE: &'static str = "E004";
+}
+
+impl Display for ConfigRefError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.config_exists {
+            let _ = write!(
+                f,
+                "Command `{}` reads `{}` from Config `{}`, but that path does not exist",
+                self.command, self.path, self.config
+            );
+        } else {
+            let _ = write!(
+                f,
+                "Command `{}` reads values from Config `{}`, but no Config with that name exists",
+                self.command, self.config
+            );
+        }
+        if let Some(line) = self.line_start {
+            let _ = writeln!(f);
+            let _ = write!(f, "    check the Command on line {}", line);
+        }
+        if !self.suggestions.is_empty() {
+            let names = self
+                .suggestions
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<String>>();
+            let _ = writeln!(f);
+            let _ = write!(f, "    did you mean {}?", names.join(" or "));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoDbError<'a> for ConfigRefError {
+    fn into_db_error(self, src: ItemSrc<'a>, item: &'a Item) -> DbError<'a> {
+        DbError::ConfigRef(ErrorRef {
+            inner: self,
+            item,
+            src,
+        })
+    }
+}
+
+///
+/// A Command or Dependency Check contains a script that is not valid shell syntax
+///
+#[derive(Debug, serde::Serialize, TypeScriptify)]
+pub struct ShellSyntaxError {
+    pub name: String,
+    pub kind: String,
+    pub message: String,
+    pub line_start: Option<u32>,
+    pub input_file: Option<PathBuf>,
+}
+
+impl ErrCode for ShellSyntaxError {
+    const CODE: &'static str = "E005";
+}
+
+impl Display for ShellSyntaxError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let _ = write!(
+            f,
+            "{} `{}` contains invalid shell syntax: {}",
+            self.kind, self.name, self.message
+        );
+        match (&self.input_file, self.line_start) {
+            (Some(pb), Some(line)) => {
+                let _ = writeln!(f);
+                let _ = write!(f, "    at {}:{}", pb.display(), line);
+            }
+            (None, Some(line)) => {
+                let _ = writeln!(f);
+                let _ = write!(f, "    on line {}", line);
+            }
+            _ => {}
+        };
+        Ok(())
+    }
+}
+
+impl<'a> IntoDbError<'a> for ShellSyntaxError {
+    fn into_db_error(self, src: ItemSrc<'a>, item: &'a Item) -> DbError<'a> {
+        DbError::ShellSyntax(ErrorRef {
+            inner: self,
+            item,
+            src,
+        })
+    }
+}
+
+///
+/// A script reads variables that are not given a value by its Env
+/// (or any Config it reads from), nor by the script itself
+///
+#[derive(Debug, serde::Serialize, TypeScriptify)]
+pub struct UnsetVarError {
+    pub name: String,
+    pub kind: String,
+    pub vars: Vec<String>,
+    pub line_start: Option<u32>,
+    pub input_file: Option<PathBuf>,
+}
+
+impl ErrCode for UnsetVarError {
+    const CODE: &'static str = "E006";
+}
+
+impl Display for UnsetVarError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let vars = self
+            .vars
+            .iter()
+            .map(|var| format!("`${}`", var))
+            .collect::<Vec<String>>();
+        let _ = write!(
+            f,
+            "{} `{}` reads {}, but nothing supplies {}",
+            self.kind,
+            self.name,
+            vars.join(", "),
+            if vars.len() == 1 { "it" } else { "them" }
+        );
+        if let Some(line) = self.line_start {
+            let _ = writeln!(f);
+            let _ = write!(f, "    check the script on line {}", line);
+        }
+        if let Some(first) = self.vars.first() {
+            let _ = writeln!(f);
+            let _ = write!(
+                f,
+                "    add {} to an Env, or give a default, eg: `${{{}:-value}}`",
+                if vars.len() == 1 { "it" } else { "them" },
+                first
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoDbError<'a> for UnsetVarError {
+    fn into_db_error(self, src: ItemSrc<'a>, item: &'a Item) -> DbError<'a> {
+        DbError::UnsetVar(ErrorRef {
             inner: self,
             item,
             src,
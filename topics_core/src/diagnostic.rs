@@ -0,0 +1,251 @@
+use crate::db_error::SerializedError;
+use crate::doc::{Doc, DocResult};
+use crate::doc_err::{DocError, Location};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+///
+/// A single problem with the documents, in a form that tools can consume.
+///
+/// `line` + `column` start at 1, like in every other message
+///
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Diagnostic {
+    pub code: Option<String>,
+    pub severity: Severity,
+    pub file: Option<PathBuf>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub message: String,
+    /// other places involved, eg: the rest of a cycle, or the other definition of a duplicate name
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<Site>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Site {
+    pub file: Option<PathBuf>,
+    pub line: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    /// see [`SerializedError::is_warning`]
+    Warning,
+}
+
+impl Diagnostic {
+    ///
+    /// For errors that don't come from a document, eg: a bad glob
+    ///
+    pub fn from_message(message: &str) -> Self {
+        Self {
+            code: None,
+            severity: Severity::Error,
+            file: None,
+            line: None,
+            column: None,
+            message: message.to_string(),
+            related: vec![],
+        }
+    }
+
+    ///
+    /// Every problem with a document that failed to load, or loaded with errors
+    ///
+    pub fn from_doc_result(doc: &DocResult<Doc>) -> Vec<Self> {
+        match doc {
+            Err(err) => vec![Self::from_doc_error(err, None)],
+            Ok(doc) => doc
+                .errors
+                .iter()
+                .map(|err| Self::from_doc_error(err, doc.source.file()))
+                .collect(),
+        }
+    }
+
+    ///
+    /// `file` is used when the error itself doesn't know where it came from
+    ///
+    pub fn from_doc_error(err: &DocError, file: Option<PathBuf>) -> Self {
+        let (file, line, column, message) = match err {
+            DocError::PathRead { pb, .. }
+            | DocError::NotSupported(pb)
+            | DocError::MissingExtension(pb) => (Some(pb.clone()), None, None, err.to_string()),
+            DocError::SerdeLocationErr(loc_err)
+            | DocError::MissingHeading(loc_err)
+            | DocError::InvalidCodeFence(loc_err)
+            | DocError::MissingContent(loc_err) => {
+                let (line, column) = match &loc_err.location {
+                    Some(Location::LineAndCol { line, column })
+                    | Some(Location::LineAndColRegion { line, column, .. }) => {
+                        (Some(*line as u32), Some(*column as u32))
+                    }
+                    Some(Location::Region { line_start, .. }) => (Some(*line_start as u32), None),
+                    None => (None, None),
+                };
+                let file = loc_err.input_file.clone().or(file);
+                (file, line, column, loc_err.description.clone())
+            }
+            DocError::Unknown(_) => (file, None, None, err.to_string()),
+        };
+        Self {
+            code: Some(err.code().to_string()),
+            file,
+            line,
+            column,
+            message,
+            ..Self::from_message("")
+        }
+    }
+
+    ///
+    /// The first site of a graph error is where it's reported, the rest are `related`
+    ///
+    pub fn from_db_error(err: &SerializedError) -> Self {
+        let mut sites = err.sites().into_iter().map(|(file, line)| Site {
+            file: file.cloned(),
+            line,
+        });
+        let first = sites.next();
+        let severity = if err.is_warning() {
+            Severity::Warning
+        } else {
+            Severity::Error
+        };
+        Self {
+            code: Some(err.code().to_string()),
+            severity,
+            file: first.as_ref().and_then(|site| site.file.clone()),
+            line: first.and_then(|site| site.line),
+            message: err.message(),
+            related: sites.collect(),
+            ..Self::from_message("")
+        }
+    }
+}
+
+pub fn to_json(diagnostics: &[Diagnostic]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(diagnostics)
+}
+
+///
+/// A SARIF 2.1.0 log, the format that code scanning UIs use to annotate lines in a PR
+///
+/// Problems that aren't in any file, eg: a bad glob, have no `locations` at all
+///
+pub fn to_sarif(diagnostics: &[Diagnostic]) -> Value {
+    let location = |file: &Option<PathBuf>, line: Option<u32>, column: Option<u32>| {
+        let mut physical = json!({
+            "artifactLocation": { "uri": file.as_ref()?.display().to_string() }
+        });
+        if let Some(line) = line {
+            physical["region"] = json!({ "startLine": line.max(1) });
+            if let Some(column) = column {
+                physical["region"]["startColumn"] = json!(column.max(1));
+            }
+        }
+        Some(json!({ "physicalLocation": physical }))
+    };
+
+    let mut rules = diagnostics
+        .iter()
+        .filter_map(|diagnostic| diagnostic.code.clone())
+        .collect::<Vec<String>>();
+    rules.sort();
+    rules.dedup();
+
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let level = match diagnostic.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            let mut result = json!({
+                "level": level,
+                "message": { "text": diagnostic.message.trim() },
+            });
+            if let Some(location) = location(&diagnostic.file, diagnostic.line, diagnostic.column) {
+                result["locations"] = json!([location]);
+            }
+            if let Some(code) = &diagnostic.code {
+                result["ruleId"] = json!(code);
+            }
+            if !diagnostic.related.is_empty() {
+                result["relatedLocations"] = diagnostic
+                    .related
+                    .iter()
+                    .filter_map(|site| location(&site.file, site.line, None))
+                    .collect();
+            }
+            result
+        })
+        .collect::<Vec<Value>>();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "topics",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<Value>>()
+                }
+            },
+            "results": results
+        }]
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::Context;
+    use crate::db::with_db;
+
+    #[test]
+    fn test_db_errors_as_sarif() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/cycle/topics.md")]);
+        let diagnostics = with_db(&f, |_db, errors| {
+            errors
+                .into_iter()
+                .map(SerializedError::from)
+                .map(|err| Diagnostic::from_db_error(&err))
+                .collect::<Vec<Diagnostic>>()
+        });
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("E001"));
+        assert_eq!(diagnostics[0].related.len(), 2);
+
+        let sarif = to_sarif(&diagnostics);
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "E001");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "../fixtures/cycle/topics.md"
+        );
+        assert_eq!(result["relatedLocations"].as_array().map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn test_message_only_sarif() {
+        let sarif = to_sarif(&[Diagnostic::from_message("no files matched")]);
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["level"], "error");
+        assert_eq!(result["locations"], Value::Null);
+    }
+
+    #[test]
+    fn test_doc_error_location() {
+        let err = DocError::NotSupported(PathBuf::from("notes.txt"));
+        let diagnostic = Diagnostic::from_doc_error(&err, None);
+        assert_eq!(diagnostic.file, Some(PathBuf::from("notes.txt")));
+        assert_eq!(diagnostic.line, None);
+        assert_eq!(diagnostic.code.as_deref(), Some(err.code()));
+    }
+}
@@ -0,0 +1,214 @@
+use globset::{Glob, GlobBuilder, GlobMatcher};
+use ignore::{DirEntry, WalkBuilder};
+use std::path::{Path, PathBuf};
+
+///
+/// The optional project file, read from the root of the [`crate::cwd::Cwd`]
+///
+/// ```toml
+/// include = ["docs/**/*.md", "checks.yaml"]
+/// exclude = ["docs/drafts/**"]
+/// ```
+///
+pub const PROJECT_FILE: &str = "topics.toml";
+
+///
+/// Every file extension that [`crate::doc::Doc::from_path_buf`] can read
+///
+pub const EXTENSIONS: &[&str] = &["md", "markdown", "yaml", "yml", "toml", "json"];
+
+///
+/// The extensions found when walking directories or matching globs, see [`discover`]
+///
+pub const DISCOVERED_EXTENSIONS: &[&str] = &["md", "markdown", "yaml", "yml"];
+
+#[derive(Debug, Default, PartialEq, serde::Deserialize)]
+pub struct Project {
+    /// globs used when no files are given on the command line
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// globs that are never loaded, unless the file is given by name
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DiscoverError {
+    #[error("could not read `{}`: {original}", .pb.display())]
+    ProjectRead {
+        pb: PathBuf,
+        original: std::io::Error,
+    },
+    #[error("`{}` is not valid: {original}", .pb.display())]
+    ProjectParse {
+        pb: PathBuf,
+        original: toml::de::Error,
+    },
+    #[error("`{pattern}` is not a valid glob: {original}")]
+    Glob {
+        pattern: String,
+        original: globset::Error,
+    },
+}
+
+impl Project {
+    ///
+    /// `Ok(None)` when there's no project file
+    ///
+    pub fn from_dir(dir: &Path) -> Result<Option<Self>, DiscoverError> {
+        let pb = dir.join(PROJECT_FILE);
+        if !pb.exists() {
+            return Ok(None);
+        }
+        let content =
+            std::fs::read_to_string(&pb).map_err(|original| DiscoverError::ProjectRead {
+                pb: pb.clone(),
+                original,
+            })?;
+        toml::from_str(&content)
+            .map(Some)
+            .map_err(|original| DiscoverError::ProjectParse { pb, original })
+    }
+}
+
+///
+/// Turn the paths given on the command line into the list of files to load.
+///
+/// - files are kept as they are, even when they'd otherwise be excluded or ignored
+/// - directories are walked recursively, for every file with one of the [`DISCOVERED_EXTENSIONS`]
+/// - anything containing `*`, `?`, `[` or `{` is a glob, matched against paths relative to `cwd`
+/// - with no paths at all, the `include` globs from `topics.toml` are used instead
+///
+/// TOML + JSON documents are only loaded when they're named, either on the command line or
+/// in `include`, since most of them will be configuration for other tools.
+///
+/// Discovered files are skipped when they match an `exclude` glob, or any `.gitignore`
+/// that applies to them, so that generated output is never read back in.
+///
+pub fn discover(cwd: &Path, inputs: &[PathBuf]) -> Result<Vec<PathBuf>, DiscoverError> {
+    let project = Project::from_dir(cwd)?.unwrap_or_default();
+    let exclude = project
+        .exclude
+        .iter()
+        .map(String::as_str)
+        .map(matcher)
+        .collect::<Result<Vec<GlobMatcher>, DiscoverError>>()?;
+
+    let inputs = if inputs.is_empty() {
+        project.include.iter().map(PathBuf::from).collect()
+    } else {
+        inputs.to_vec()
+    };
+
+    let mut output: Vec<PathBuf> = vec![];
+    for input in inputs {
+        let found = match input.to_str() {
+            Some(pattern) if is_glob(pattern) => {
+                let glob = matcher(pattern)?;
+                walk(cwd, cwd, &exclude)
+                    .into_iter()
+                    .filter(|rel| glob.is_match(rel))
+                    .collect()
+            }
+            _ if cwd.join(&input).is_dir() => walk(cwd, &cwd.join(&input), &exclude),
+            _ => vec![input.clone()],
+        };
+        for pb in found {
+            if !output.contains(&pb) {
+                output.push(pb);
+            }
+        }
+    }
+    Ok(output)
+}
+
+///
+/// Every discoverable file beneath `dir`, as paths relative to `cwd`, in a stable order
+///
+fn walk(cwd: &Path, dir: &Path, exclude: &[GlobMatcher]) -> Vec<PathBuf> {
+    let root = cwd.to_path_buf();
+    let rel = move |path: &Path| path.strip_prefix(&root).unwrap_or(path).to_path_buf();
+    let skip = {
+        let rel = rel.clone();
+        let exclude = exclude.to_vec();
+        move |entry: &DirEntry| {
+            entry.depth() > 0
+                && (entry.file_name() == ".git"
+                    || exclude.iter().any(|glob| glob.is_match(rel(entry.path()))))
+        }
+    };
+    WalkBuilder::new(dir)
+        .hidden(false)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |entry| !skip(entry))
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map_or(false, |ft| ft.is_file()))
+        .filter(|entry| entry.file_name() != PROJECT_FILE)
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map_or(false, |ext| DISCOVERED_EXTENSIONS.contains(&ext))
+        })
+        .map(|entry| rel(entry.path()))
+        .collect()
+}
+
+fn is_glob(input: &str) -> bool {
+    input.contains(|c: char| matches!(c, '*' | '?' | '[' | '{'))
+}
+
+///
+/// `*` never crosses a `/`, only `**` does - the same as in `.gitignore` files
+///
+fn matcher(pattern: &str) -> Result<GlobMatcher, DiscoverError> {
+    glob(pattern).map(|glob| glob.compile_matcher())
+}
+
+fn glob(pattern: &str) -> Result<Glob, DiscoverError> {
+    GlobBuilder::new(pattern.trim_start_matches("./"))
+        .literal_separator(true)
+        .build()
+        .map_err(|original| DiscoverError::Glob {
+            pattern: pattern.to_string(),
+            original,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_discover_dir_and_globs() {
+        let cwd = PathBuf::from("../fixtures/discover");
+        let all = discover(&cwd, &[]).expect("discover");
+        assert_eq!(
+            all,
+            vec![
+                PathBuf::from("docs/checks.yaml"),
+                PathBuf::from("docs/nested/more.md"),
+                PathBuf::from("docs/setup.md"),
+            ]
+        );
+        let md = discover(&cwd, &[PathBuf::from("**/*.md")]).expect("discover");
+        assert_eq!(
+            md,
+            vec![
+                PathBuf::from("docs/nested/more.md"),
+                PathBuf::from("docs/setup.md"),
+            ]
+        );
+        // named files are always kept
+        let named = discover(&cwd, &[PathBuf::from("docs/drafts/wip.md")]).expect("discover");
+        assert_eq!(named, vec![PathBuf::from("docs/drafts/wip.md")]);
+        // toml is only loaded by name
+        let toml = discover(&cwd, &[PathBuf::from("docs/*.toml")]).expect("discover");
+        assert!(toml.is_empty());
+        let named = discover(&cwd, &[PathBuf::from("docs/ci.toml")]).expect("discover");
+        assert_eq!(named, vec![PathBuf::from("docs/ci.toml")]);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::doc_err::DocError;
-use crate::doc_src::{from_serde_yaml_error, DocSource, MdSrc, TomlError};
+use crate::doc_src::DocSource;
 use crate::items::item::Item;
 
 use crate::context::Context;
@@ -8,6 +8,7 @@ use std::path::PathBuf;
 #[derive(Debug, Default)]
 pub struct Doc {
     pub source: DocSource,
+    pub items: Vec<Item>,
     pub errors: Vec<DocError>,
 }
 
@@ -63,57 +64,46 @@ impl Doc {
     pub fn from_path_buf(pb: impl Into<PathBuf>, ctx: &Context) -> DocResult<Self> {
         let pb = pb.into();
         let doc_src = match pb.extension() {
-            None => todo!("what to handle here?"),
+            None => return Err(DocError::MissingExtension(pb)),
             Some(os_str) => match os_str.to_str() {
-                None => todo!("what to handle here?"),
+                None => return Err(DocError::NotSupported(pb)),
                 Some("yaml") | Some("yml") => DocSource::yaml(&pb, ctx)?,
                 Some("toml") => DocSource::toml(&pb, ctx)?,
+                Some("json") => DocSource::json(&pb, ctx)?,
                 Some("md") | Some("markdown") => DocSource::md(&pb, ctx)?,
                 Some(_other) => return Err(DocError::NotSupported(pb)),
             },
         };
         Self::from_doc_src(&pb, doc_src, &ctx)
     }
-    pub fn from_doc_src(_pb: &PathBuf, doc_src: DocSource, _ctx: &Context) -> DocResult<Self> {
-        let doc = Doc {
+    ///
+    /// Parse content that has not been written to `pb` yet, see [`DocSource::from_content`]
+    ///
+    pub fn from_content(pb: impl Into<PathBuf>, content: &str, ctx: &Context) -> DocResult<Self> {
+        let pb = pb.into();
+        let doc_src = DocSource::from_content(&pb, content)?;
+        Self::from_doc_src(&pb, doc_src, ctx)
+    }
+    pub fn from_doc_src(_pb: &PathBuf, mut doc_src: DocSource, _ctx: &Context) -> DocResult<Self> {
+        // markdown items are kept with each document of the source, see `with_db`
+        let md_errors = match &mut doc_src {
+            DocSource::Md(md_doc) => md_doc.read_items(),
+            _ => vec![],
+        };
+        let mut doc = Doc {
             source: doc_src,
             ..Default::default()
         };
+        let (items, errors) = match &doc.source {
+            DocSource::Yaml(yaml_doc) => yaml_doc.parse_items(&doc),
+            DocSource::Toml(toml_doc) => toml_doc.parse_items(&doc),
+            DocSource::Json(json_doc) => json_doc.parse_items(&doc),
+            DocSource::Md(_) => (vec![], md_errors),
+        };
+        doc.items = items;
+        doc.errors = errors;
         Ok(doc)
         //     match &doc.source {
-        //         DocSource::Yaml(yaml_doc) => {
-        //             // for src in &yaml_doc.doc_src_items.items {
-        //             //     let item: Result<Item, DocError> = serde_yaml::from_str(&src.content)
-        //             //         .map_err(|err| from_serde_yaml_error(&doc, &src, &err));
-        //             //     match item {
-        //             //         Err(doc_err) => {
-        //             //             doc.errors.push(doc_err);
-        //             //         }
-        //             //         Ok(item) => {
-        //             //             doc.items.push(ItemTracked {
-        //             //                 item,
-        //             //                 src_doc: DocSource::Yaml((*yaml_doc).clone()),
-        //             //                 input_file: yaml_doc.input_file.clone(),
-        //             //             });
-        //             //         }
-        //             //     };
-        //             // }
-        //             todo!("yaml input?")
-        //         }
-        //         DocSource::Toml(toml_doc) => {
-        //             // let items = one_or_many_toml(&toml_doc.file_content).map_err(|err| TomlError {
-        //             //     doc: &doc,
-        //             //     toml_err: err,
-        //             // })?;
-        //             // for item in items {
-        //             //     doc.items.push(ItemTracked {
-        //             //         item,
-        //             //         src_doc: DocSource::Toml((*toml_doc).clone()),
-        //             //         input_file: toml_doc.input_file.clone(),
-        //             //     });
-        //             // }
-        //             todo!("toml input...?");
-        //         }
         //         DocSource::Md(md_doc) => {
         //             // let mds: Vec<Result<MdSrc, _>> = md_doc
         //             //     .doc_src_items
@@ -151,26 +141,110 @@ impl Doc {
     }
 }
 
-// fn one_or_many_toml(input: &str) -> Result<Vec<Item>, toml::de::Error> {
-//     #[derive(Debug, serde::Deserialize)]
-//     struct TempItems {
-//         item: Vec<Item>,
-//     }
-//     toml::from_str::<TempItems>(input)
-//         .or_else(|err| {
-//             if err
-//                 .to_string()
-//                 .contains("missing field `item` at line 1 column 1")
-//             {
-//                 toml::from_str::<Item>(input).map(|item| TempItems { item: vec![item] })
-//             } else {
-//                 Err(err)
-//             }
-//         })
-//         .map(|temp| temp.item)
-// }
-
 // #[test]
 // fn test_from_path() {
 //     let d = Doc::from_path_buf("../fixtures/md/topics.md");
 // }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::doc_err::Location;
+
+    fn location(err: &DocError) -> Option<(usize, &str)> {
+        match err {
+            DocError::MissingHeading(loc_err)
+            | DocError::InvalidCodeFence(loc_err)
+            | DocError::SerdeLocationErr(loc_err)
+            | DocError::MissingContent(loc_err) => match loc_err.location {
+                Some(Location::LineAndCol { line, .. }) => {
+                    Some((line, loc_err.description.as_str()))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_missing_heading() {
+        let ctx = Context::default();
+        let md = "# Command: first\n\n```shell\necho hi\n```\n\n---\n\nSome intro text\n\n# Command: second\n";
+        let doc = Doc::from_content("topics.md", md, &ctx).expect("readable");
+        assert_eq!(doc.errors.len(), 1);
+        assert_eq!(doc.errors[0].code(), "E105");
+        assert_eq!(
+            location(&doc.errors[0]),
+            Some((
+                9,
+                "expected a level 1 heading such as `# Command: Run tests`, but found a paragraph"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_invalid_code_fence() {
+        let ctx = Context::default();
+        let md = "# Dependency Check: node\n\n```shell verify --dir ./app\nnode --version\n```\n";
+        let doc = Doc::from_content("topics.md", md, &ctx).expect("readable");
+        assert_eq!(doc.errors.len(), 1);
+        assert_eq!(doc.errors[0].code(), "E106");
+        let (line, description) = location(&doc.errors[0]).expect("location");
+        assert_eq!(line, 3);
+        assert!(description.starts_with("invalid code fence `shell verify --dir ./app`: "));
+    }
+
+    #[test]
+    fn test_misspelt_code_fence() {
+        let ctx = Context::default();
+        let md = "# Dependency Check: node\n\n```shell verfy\nnode --version\n```\n\n```shell\necho docs only\n```\n";
+        let doc = Doc::from_content("topics.md", md, &ctx).expect("readable");
+        assert_eq!(doc.errors.len(), 1);
+        assert_eq!(doc.errors[0].code(), "E106");
+        let (line, description) = location(&doc.errors[0]).expect("location");
+        assert_eq!(line, 3);
+        assert_eq!(
+            description,
+            "invalid code fence `shell verfy`: unknown name `verfy`, did you mean `verify`?"
+        );
+    }
+
+    #[test]
+    fn test_invalid_toml() {
+        let ctx = Context::default();
+        let md = "# Command: deploy
+
+```shell
+./deploy.sh
+```
+
+```toml
+NODE_ENV = \"production\"\nDEBUG = \n```
+";
+        let doc = Doc::from_content("topics.md", md, &ctx).expect("readable");
+        assert_eq!(doc.errors.len(), 1);
+        assert_eq!(doc.errors[0].code(), "E102");
+        let (line, description) = location(&doc.errors[0]).expect("location");
+        assert_eq!(line, 9);
+        assert!(description.starts_with("invalid TOML: "));
+        assert!(!description.contains(" at line "));
+    }
+
+    #[test]
+    fn test_missing_path() {
+        let ctx = Context::default();
+        let md = "# Command: first\n\n```shell\necho hi\n```\n\n---\n\n# File Exists Check: npm config\n\nCheck that the npm config exists\n";
+        let doc = Doc::from_content("topics.md", md, &ctx).expect("readable");
+        assert_eq!(doc.errors.len(), 1);
+        assert_eq!(doc.errors[0].code(), "E108");
+        let (line, _) = location(&doc.errors[0]).expect("location");
+        assert_eq!(line, 9);
+    }
+
+    #[test]
+    fn test_missing_extension() {
+        let ctx = Context::default();
+        let err = Doc::from_path_buf("docs/SETUP", &ctx).expect_err("no extension");
+        assert_eq!(err.code(), "E107");
+    }
+}
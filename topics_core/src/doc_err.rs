@@ -26,6 +26,32 @@ pub enum DocError {
     Unknown(String),
     #[error("File format not supported: {}", .0.display())]
     NotSupported(PathBuf),
+    #[error("{}", .0.description)]
+    MissingHeading(LocationError),
+    #[error("{}", .0.description)]
+    InvalidCodeFence(LocationError),
+    #[error("`{}` has no file extension, so its format is unknown", .0.display())]
+    MissingExtension(PathBuf),
+    #[error("{}", .0.description)]
+    MissingContent(LocationError),
+}
+
+impl DocError {
+    ///
+    /// Stable codes for problems reading a document, graph errors use `0xx`
+    ///
+    pub fn code(&self) -> &'static str {
+        match self {
+            DocError::PathRead { .. } => "E101",
+            DocError::SerdeLocationErr(_) => "E102",
+            DocError::Unknown(_) => "E103",
+            DocError::NotSupported(_) => "E104",
+            DocError::MissingHeading(_) => "E105",
+            DocError::InvalidCodeFence(_) => "E106",
+            DocError::MissingExtension(_) => "E107",
+            DocError::MissingContent(_) => "E108",
+        }
+    }
 }
 
 impl From<anyhow::Error> for DocError {
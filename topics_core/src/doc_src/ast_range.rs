@@ -32,6 +32,21 @@ impl AstRange {
             ast_len: len,
         }
     }
+    ///
+    /// The same range minus its first node (the heading), or `None` when nothing would be left,
+    /// eg: `([0], 3)` -> `([0, 1], 2)`
+    ///
+    pub fn without_heading(&self) -> Option<AstRange> {
+        if self.ast_path.is_empty() || self.ast_len < 2 {
+            return None;
+        }
+        let mut ast_path = self.ast_path.clone();
+        match ast_path.len() {
+            1 => ast_path.push(1),
+            _ => *ast_path.last_mut().unwrap() += 1,
+        }
+        Some(AstRange::range(&ast_path, self.ast_len - 1))
+    }
 }
 
 impl AstRangeImpl for AstRange {
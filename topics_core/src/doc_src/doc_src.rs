@@ -1,8 +1,10 @@
 use crate::context::Context;
 use crate::doc::DocResult;
-use crate::doc_src::{MdDocSource, TomlDocSource, YamlDocSource};
+use crate::doc_err::DocError;
+use crate::doc_src::{JsonDocSource, MdDocSource, TomlDocSource, YamlDocSource};
 
 use std::path::PathBuf;
+use std::str::FromStr;
 
 pub trait DocSrcImpl: Sized {
     fn from_path_buf(pb: &PathBuf, ctx: &Context) -> DocResult<Self>;
@@ -12,6 +14,7 @@ pub trait DocSrcImpl: Sized {
 pub enum DocSource {
     Yaml(YamlDocSource),
     Toml(TomlDocSource),
+    Json(JsonDocSource),
     Md(MdDocSource),
 }
 
@@ -20,6 +23,7 @@ impl DocSource {
         match self {
             DocSource::Yaml(yaml_doc) => yaml_doc.input_file.clone(),
             DocSource::Toml(toml_doc) => toml_doc.input_file.clone(),
+            DocSource::Json(json_doc) => json_doc.input_file.clone(),
             DocSource::Md(md_doc) => md_doc.input_file.clone(),
         }
     }
@@ -27,6 +31,7 @@ impl DocSource {
         match self {
             DocSource::Yaml(yaml_doc) => yaml_doc.file_content.as_str(),
             DocSource::Toml(toml_doc) => toml_doc.file_content.as_str(),
+            DocSource::Json(json_doc) => json_doc.file_content.as_str(),
             DocSource::Md(md_doc) => md_doc.file_content.as_str(),
         }
     }
@@ -36,9 +41,39 @@ impl DocSource {
     pub fn toml(pb: &PathBuf, ctx: &Context) -> DocResult<Self> {
         Ok(DocSource::Toml(TomlDocSource::from_path_buf(&pb, ctx)?))
     }
+    pub fn json(pb: &PathBuf, ctx: &Context) -> DocResult<Self> {
+        Ok(DocSource::Json(JsonDocSource::from_path_buf(&pb, ctx)?))
+    }
     pub fn md(pb: &PathBuf, ctx: &Context) -> DocResult<Self> {
         Ok(DocSource::Md(MdDocSource::from_path_buf(&pb, ctx)?))
     }
+    ///
+    /// The same as the constructors above, but for content that's already in memory
+    /// (eg: unsaved changes in an editor). The extension of `pb` decides the format
+    ///
+    pub fn from_content(pb: &PathBuf, content: &str) -> DocResult<Self> {
+        let input_file = Some(pb.clone());
+        match pb.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => Ok(DocSource::Yaml(YamlDocSource {
+                input_file,
+                ..YamlDocSource::from_str(content)?
+            })),
+            Some("toml") => Ok(DocSource::Toml(TomlDocSource {
+                input_file,
+                ..TomlDocSource::from_str(content)?
+            })),
+            Some("json") => Ok(DocSource::Json(JsonDocSource {
+                input_file,
+                ..JsonDocSource::from_str(content)?
+            })),
+            Some("md") | Some("markdown") => Ok(DocSource::Md(MdDocSource {
+                input_file,
+                ..MdDocSource::from_str(content)?
+            })),
+            Some(_) => Err(DocError::NotSupported(pb.clone())),
+            None => Err(DocError::MissingExtension(pb.clone())),
+        }
+    }
 }
 
 impl Default for DocSource {
@@ -0,0 +1,54 @@
+use crate::doc::Doc;
+use crate::doc_src::{DocSource, MdSrc};
+use std::path::PathBuf;
+
+///
+/// Where an item in the graph came from.
+///
+/// Markdown items keep a reference to their parsed AST so that
+/// sections of it can be rendered later. Items from every other format
+/// were deserialized directly, so only the [`Doc`] is kept.
+///
+#[derive(Debug, Clone, Copy)]
+pub enum ItemSrc<'a> {
+    Md(&'a MdSrc<'a>),
+    Doc(&'a Doc),
+}
+
+impl<'a> ItemSrc<'a> {
+    pub fn input_file(&self) -> Option<&'a PathBuf> {
+        match *self {
+            ItemSrc::Md(md_src) => md_src.md_doc_src.input_file.as_ref(),
+            ItemSrc::Doc(doc) => match &doc.source {
+                DocSource::Yaml(yaml_doc) => yaml_doc.input_file.as_ref(),
+                DocSource::Toml(toml_doc) => toml_doc.input_file.as_ref(),
+                DocSource::Json(json_doc) => json_doc.input_file.as_ref(),
+                DocSource::Md(md_doc) => md_doc.input_file.as_ref(),
+            },
+        }
+    }
+    pub fn file_content(&self) -> &'a str {
+        match *self {
+            ItemSrc::Md(md_src) => md_src.md_doc_src.file_content.as_str(),
+            ItemSrc::Doc(doc) => doc.source.content(),
+        }
+    }
+    ///
+    /// Convert a line marker into a line within the whole file.
+    ///
+    /// Only markdown items record lines relative to the document they
+    /// were split from, everything else is already relative to the file.
+    ///
+    pub fn line_in_file(&self, line: Option<u32>) -> Option<u32> {
+        match *self {
+            ItemSrc::Md(md_src) => md_src.line_in_file(line),
+            ItemSrc::Doc(_) => line,
+        }
+    }
+    pub fn md(&self) -> Option<&'a MdSrc<'a>> {
+        match *self {
+            ItemSrc::Md(md_src) => Some(md_src),
+            ItemSrc::Doc(_) => None,
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::context::Context;
+use crate::doc::{Doc, DocResult};
+use crate::doc_err::{DocError, Location, LocationError};
+use crate::doc_src::yaml_doc_src::{with_lines, Marked};
+use crate::doc_src::DocSrcImpl;
+use crate::items::Item;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Default)]
+pub struct JsonDocSource {
+    pub input_file: Option<PathBuf>,
+    pub file_content: String,
+}
+
+impl DocSrcImpl for JsonDocSource {
+    fn from_path_buf(pb: &PathBuf, ctx: &Context) -> DocResult<Self> {
+        let abs = ctx.join_path(pb);
+        let file_str = std::fs::read_to_string(&abs).map_err(|e| DocError::PathRead {
+            pb: pb.clone(),
+            abs: abs.clone(),
+            original: e,
+        })?;
+        let new_self = Self {
+            input_file: Some(pb.clone()),
+            file_content: file_str,
+        };
+        Ok(new_self)
+    }
+}
+
+impl JsonDocSource {
+    ///
+    /// JSON documents use the same `kind` + `content` shape that
+    /// items are serialized to. Any of the following are accepted:
+    ///
+    /// - a single item `{ "kind": "Command", "content": { .. } }`
+    /// - an array of items
+    /// - the output of `topics print -p json`, where only `items` is read
+    ///
+    pub fn parse_items(&self, doc: &Doc) -> (Vec<Item>, Vec<DocError>) {
+        match one_or_many_json(&self.file_content) {
+            Ok(items) => (with_json_lines(&self.file_content, items), vec![]),
+            Err(json_err) => (vec![], vec![DocError::from(JsonError { doc, json_err })]),
+        }
+    }
+}
+
+fn one_or_many_json(input: &str) -> Result<Vec<Item>, serde_json::Error> {
+    #[derive(Debug, serde::Deserialize)]
+    struct TempItems {
+        items: Vec<Item>,
+    }
+    // parsing into a plain value first means syntax errors are reported before
+    // deciding which shape the document has. Each shape is then parsed from the
+    // original input again so that errors still carry a line + column
+    let value = serde_json::from_str::<serde_json::Value>(input)?;
+    if value.is_array() {
+        serde_json::from_str::<Vec<Item>>(input)
+    } else if value.get("items").is_some() {
+        serde_json::from_str::<TempItems>(input).map(|temp| temp.items)
+    } else {
+        serde_json::from_str::<Item>(input).map(|item| vec![item])
+    }
+}
+
+///
+/// Items printed by `topics print -p json` still carry the lines of the
+/// .md/.yaml files they were first read from, so those are replaced with
+/// lines in this document. JSON is valid YAML, so the same parser is used
+///
+fn with_json_lines(input: &str, items: Vec<Item>) -> Vec<Item> {
+    let root = Marked::parse(input);
+    // the same shapes as `one_or_many_json`
+    let marks: Vec<&Marked> = match &root {
+        Some(seq @ Marked::Seq(..)) => seq.entries().iter().collect(),
+        Some(map) => match map.get("items") {
+            Some(items) => items.entries().iter().collect(),
+            None => vec![map],
+        },
+        None => vec![],
+    };
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut item)| {
+            item.clear_line_starts();
+            match marks.get(index) {
+                Some(mark) => with_lines(item, mark, 0, |node| node.get("content")),
+                None => item,
+            }
+        })
+        .collect()
+}
+
+impl FromStr for JsonDocSource {
+    type Err = DocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            input_file: None,
+            file_content: s.to_string(),
+        })
+    }
+}
+
+pub struct JsonError<'a> {
+    pub doc: &'a Doc,
+    pub json_err: serde_json::Error,
+}
+
+impl<'a> From<JsonError<'a>> for DocError {
+    fn from(JsonError { json_err, doc }: JsonError<'a>) -> Self {
+        let location = if json_err.line() > 0 {
+            Some(Location::LineAndCol {
+                line: json_err.line(),
+                column: json_err.column(),
+            })
+        } else {
+            None
+        };
+        DocError::SerdeLocationErr(LocationError {
+            input_file_src: doc.source.content().to_string(),
+            location,
+            input_file: doc.source.file(),
+            description: json_err.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::context::Context;
+    use crate::doc::Doc;
+    use crate::doc_err::{DocError, Location, LocationError};
+    use crate::doc_src::{DocSource, JsonDocSource};
+    use crate::items::{Item, ItemWrap};
+    use std::path::PathBuf;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_item_array() -> anyhow::Result<()> {
+        let ctx = Context::from_vec(&[]);
+        let doc = Doc::from_path_buf("../fixtures/json/items.json", &ctx)?;
+        assert!(doc.errors.is_empty());
+        assert_eq!(doc.items.len(), 2);
+        match doc.items.get(0) {
+            Some(Item::Topic(topic)) => {
+                assert_eq!(topic.name.item, "Run unit tests");
+                match topic.steps.get(0) {
+                    Some(ItemWrap::NamedRef(named_ref)) => {
+                        assert_eq!(named_ref.item, "Run unit tests command")
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_location() -> anyhow::Result<()> {
+        let pb = PathBuf::from("/input.json");
+        let input = r#"{
+  "kind": "Command",
+  "content": {
+    "name": "Run unit tests"
+    "command": "cargo test"
+  }
+}"#;
+        let src = JsonDocSource::from_str(input)?;
+        let doc = Doc::from_doc_src(&pb, DocSource::Json(src), &Default::default())?;
+        assert!(doc.items.is_empty());
+        match doc.errors.get(0) {
+            Some(DocError::SerdeLocationErr(LocationError {
+                location: Some(Location::LineAndCol { line, .. }),
+                ..
+            })) => assert_eq!(*line, 5),
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+}
@@ -1,17 +1,74 @@
 use std::fmt;
 use std::fmt::{Debug, Formatter};
 
-use comrak::nodes::{Ast, AstNode, NodeCodeBlock, NodeHeading, NodeValue};
+use comrak::nodes::{Ast, AstNode, NodeHeading, NodeValue};
 
 use crate::doc_src::ast_range::AstRange;
-use crate::doc_src::parse_inline_kind;
-use crate::items::{Command, Instruction, Item, ItemWrap, LineMarker};
+use crate::doc_src::{parse_inline_kind, split_first_line};
+use crate::items::{Command, Env, HostEntry, Instruction, Item, ItemWrap, LineMarker};
 use comrak::arena_tree::Node;
+use comrak::{format_commonmark, ComrakOptions};
 use std::cell::RefCell;
+use std::path::PathBuf;
+use std::str::FromStr;
 
-pub(crate) fn process_node<'a>(node: &'a AstNode<'a>, path: &mut Vec<usize>) -> Vec<Item> {
+///
+/// Something in a markdown document that prevents an item from being read,
+/// `line` is relative to the document, like the line markers on items
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum MdProblem {
+    MissingHeading {
+        line: u32,
+        found: String,
+    },
+    InvalidCodeFence {
+        line: u32,
+        info: String,
+        message: String,
+    },
+    InvalidToml {
+        line: u32,
+        message: String,
+    },
+    /// a File Exists Check without any `inline code` to give the path
+    MissingPath {
+        line: u32,
+    },
+}
+
+///
+/// `fence_line` is where the ```toml code block starts, errors from the toml
+/// parser are relative to the line after it
+///
+fn invalid_toml(fence_line: u32, err: anyhow::Error) -> MdProblem {
+    let message = err.to_string();
+    match err
+        .downcast_ref::<toml::de::Error>()
+        .and_then(|e| e.line_col())
+    {
+        Some((line, _)) => MdProblem::InvalidToml {
+            line: fence_line + 1 + line as u32,
+            message: message
+                .split(" at line ")
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        },
+        None => MdProblem::InvalidToml {
+            line: fence_line,
+            message,
+        },
+    }
+}
+
+pub(crate) fn process_node<'a>(
+    node: &'a AstNode<'a>,
+    path: &mut Vec<usize>,
+) -> (Vec<Item>, Vec<MdProblem>) {
     let mut kind: Option<Item> = None;
     let mut items: Vec<Item> = vec![];
+    let mut problems: Vec<MdProblem> = vec![];
     let first = node.children().take(1).nth(0);
     // let mut other = node.children().skip(1);
 
@@ -28,35 +85,60 @@ pub(crate) fn process_node<'a>(node: &'a AstNode<'a>, path: &mut Vec<usize>) ->
                     item.set_line_start(start_line)
                 }
             }
-            _ => {
-                todo!("handle other 'first' elements?")
-            }
+            other => problems.push(MdProblem::MissingHeading {
+                line: ast.start_line,
+                found: describe(other),
+            }),
         }
     }
 
     if let Some(Item::Instruction(inst)) = kind.as_mut() {
-        let Instruction { name: _, ast_range } = inst;
+        let Instruction { ast_range, .. } = inst;
         *ast_range = AstRange::range(&path, node.children().count());
     }
 
     // todo: probably select many command, for MVP just select the first one seen
     if let Some(Item::Command(cmd)) = kind.as_mut() {
+        // a ```toml code block holds the command's env, see `Env::from_str`
+        for node in node.children() {
+            let d = node.data.borrow();
+            if let NodeValue::CodeBlock(code_block) = &d.value {
+                if code_block.fenced && is_toml(&code_block.info) {
+                    let content = std::str::from_utf8(&code_block.literal).unwrap();
+                    match Env::from_str(content) {
+                        Ok(env) => cmd.env = Some(env),
+                        Err(e) => problems.push(invalid_toml(d.start_line, e)),
+                    }
+                }
+            }
+        }
         // find a sibling `code block` that we can use as the 'command'
         let node = node.children().enumerate().find_map(|(index, node)| {
             let d = node.data.borrow();
-            match d.value {
-                NodeValue::CodeBlock(NodeCodeBlock { fenced: true, .. }) => Some((index, d)),
-                _ => None,
+            let is_command = matches!(
+                &d.value,
+                NodeValue::CodeBlock(code_block) if code_block.fenced && !is_toml(&code_block.info)
+            );
+            if is_command {
+                Some((index, d))
+            } else {
+                None
             }
         });
 
         if let Some((index, node)) = node {
             if let NodeValue::CodeBlock(code_block) = &node.value {
                 let content = std::str::from_utf8(&code_block.literal).unwrap().trim();
-                cmd.with_content(content);
+                cmd.with_content(content, node.start_line);
                 if !code_block.info.is_empty() {
                     let info = std::str::from_utf8(&code_block.info).unwrap().trim();
-                    cmd.with_cli_params(info);
+                    if let Err(e) = cmd.with_cli_params(info) {
+                        problems.push(MdProblem::InvalidCodeFence {
+                            line: node.start_line,
+                            info: info.to_string(),
+                            message: e.to_string(),
+                        });
+                    }
                 }
                 let mut next_range = path.to_vec();
                 next_range.push(index);
@@ -65,6 +147,96 @@ pub(crate) fn process_node<'a>(node: &'a AstNode<'a>, path: &mut Vec<usize>) ->
         }
     }
 
+    // values come from the first ```toml code block
+    if let Some(Item::Config(config)) = kind.as_mut() {
+        let content = node
+            .children()
+            .find_map(|node| match &node.data.borrow().value {
+                NodeValue::CodeBlock(code_block)
+                    if code_block.fenced && is_toml(&code_block.info) =>
+                {
+                    Some((
+                        std::str::from_utf8(&code_block.literal)
+                            .unwrap()
+                            .to_string(),
+                        node.data.borrow().start_line,
+                    ))
+                }
+                _ => None,
+            });
+        if let Some((content, line)) = content {
+            if let Err(e) = config.with_toml(&content) {
+                problems.push(invalid_toml(line, e));
+            }
+        }
+    }
+
+    // `verify` + `autofix` scripts both come from code blocks, eg: ```shell verify
+    if let Some(Item::DependencyCheck(dep_check)) = kind.as_mut() {
+        for node in node.children() {
+            let d = node.data.borrow();
+            if let NodeValue::CodeBlock(code_block) = &d.value {
+                if code_block.fenced && !code_block.info.is_empty() {
+                    let content = std::str::from_utf8(&code_block.literal).unwrap().trim();
+                    let info = std::str::from_utf8(&code_block.info).unwrap().trim();
+                    if let Err(e) = dep_check.with_content(content, info, d.start_line) {
+                        problems.push(MdProblem::InvalidCodeFence {
+                            line: d.start_line,
+                            info: info.to_string(),
+                            message: e.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // a Task Group's steps come from its first list, with or without a `## Steps` heading
+    if let Some(Item::TaskGroup(tg)) = kind.as_mut() {
+        if let Some(list) = first_list(node) {
+            tg.steps.extend(
+                list.children()
+                    .filter_map(|entry| list_item_wrap(entry, &mut problems)),
+            );
+        }
+    }
+
+    // the path comes from the first `inline code` after the heading, eg: Check that `.npmrc` exists
+    if let Some(Item::FileExistsCheck(fec)) = kind.as_mut() {
+        let path = node
+            .children()
+            .skip(1)
+            .flat_map(|node| node.descendants())
+            .find_map(|node| match &node.data.borrow().value {
+                NodeValue::Code(code) => Some(String::from_utf8_lossy(code).to_string()),
+                _ => None,
+            });
+        match path {
+            Some(path) => fec.path = PathBuf::from(path),
+            None => problems.push(MdProblem::MissingPath {
+                line: fec.name.line_start.unwrap_or(1),
+            }),
+        }
+    }
+
+    // each entry in the first list is a domain, optionally written as a full line
+    // from the hosts file, eg: `127.0.0.1 app.local`
+    if let Some(Item::HostEntriesCheck(hec)) = kind.as_mut() {
+        if let Some(list) = first_list(node) {
+            for entry in list.children() {
+                let text = entry
+                    .first_child()
+                    .map(collect_single_line_text)
+                    .unwrap_or_default();
+                if let Some(domain) = text.split_whitespace().last() {
+                    hec.hosts.push(HostEntry {
+                        domain: domain.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
     if let Some(Item::Topic(topic)) = kind.as_mut() {
         let mut list: Vec<(&'_ Node<RefCell<Ast>>, Option<&'_ Node<RefCell<Ast>>>)> = vec![];
         node.children().enumerate().for_each(|(_index, node)| {
@@ -94,14 +266,10 @@ pub(crate) fn process_node<'a>(node: &'a AstNode<'a>, path: &mut Vec<usize>) ->
                     let d = node.data.borrow();
                     match &d.value {
                         NodeValue::Item(_list) => {
-                            for node in node.children() {
-                                let named_ref = collect_single_line_text(node);
-                                let item_line_start = node.data.borrow();
+                            if let Some(item_wrap) = list_item_wrap(node, &mut problems) {
                                 match heading_kind.as_str() {
-                                    "Steps" => topic
-                                        .add_step_named_ref(named_ref, item_line_start.start_line),
-                                    "Dependencies" => topic
-                                        .add_dep_named_ref(named_ref, item_line_start.start_line),
+                                    "Steps" => topic.steps.push(item_wrap),
+                                    "Dependencies" => topic.deps.push(item_wrap),
                                     _ => {}
                                 }
                             }
@@ -117,7 +285,169 @@ pub(crate) fn process_node<'a>(node: &'a AstNode<'a>, path: &mut Vec<usize>) ->
         items.push(kind)
     }
 
-    items
+    (items, problems)
+}
+
+///
+/// How to refer to a block-level element in an error, eg: `a paragraph`
+///
+fn describe(value: &NodeValue) -> String {
+    match value {
+        NodeValue::Heading(NodeHeading { level, .. }) => format!("a level {} heading", level),
+        NodeValue::Paragraph => String::from("a paragraph"),
+        NodeValue::List(_) => String::from("a list"),
+        NodeValue::CodeBlock(_) => String::from("a code block"),
+        NodeValue::BlockQuote => String::from("a block quote"),
+        NodeValue::HtmlBlock(_) => String::from("some html"),
+        NodeValue::ThematicBreak => String::from("a horizontal rule"),
+        NodeValue::Table(_) => String::from("a table"),
+        _ => String::from("something other than a heading"),
+    }
+}
+
+///
+/// A single entry in a Topic's list. Usually this is a reference to another item by name,
+/// but Commands, Instructions + Dependency Checks can also be defined in place:
+///
+/// ````md
+/// - Command: build the app
+///
+///   ```shell command --cwd="./app"
+///   yarn build
+///   ```
+/// ````
+///
+fn list_item_wrap<'a>(
+    list_item: &'a AstNode<'a>,
+    problems: &mut Vec<MdProblem>,
+) -> Option<ItemWrap> {
+    let first = list_item.first_child()?;
+    let line_start = first.data.borrow().start_line;
+    let (first_line, rest_of_paragraph) = split_paragraph(first);
+    let mut item = match split_first_line(&first_line) {
+        Some(item @ Item::Command(_))
+        | Some(item @ Item::Instruction(_))
+        | Some(item @ Item::DependencyCheck(_)) => item,
+        _ => {
+            let named_ref = collect_single_line_text(first);
+            return Some(ItemWrap::named_ref(named_ref, line_start));
+        }
+    };
+    item.set_line_start(line_start);
+    let rest = list_item.children().skip(1);
+    match &mut item {
+        Item::Command(cmd) => {
+            // like top-level Commands, only the first non-toml code block is used
+            let mut has_command = false;
+            for (info, content, line) in fenced_blocks(rest) {
+                if is_toml(info.as_bytes()) {
+                    match Env::from_str(&content) {
+                        Ok(env) => cmd.env = Some(env),
+                        Err(e) => problems.push(invalid_toml(line, e)),
+                    }
+                } else if !has_command {
+                    has_command = true;
+                    cmd.with_content(content.trim(), line);
+                    if !info.is_empty() {
+                        if let Err(e) = cmd.with_cli_params(&info) {
+                            problems.push(MdProblem::InvalidCodeFence {
+                                line,
+                                info,
+                                message: e.to_string(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Item::DependencyCheck(dep_check) => {
+            for (info, content, line) in fenced_blocks(rest) {
+                if !info.is_empty() {
+                    if let Err(e) = dep_check.with_content(content.trim(), &info, line) {
+                        problems.push(MdProblem::InvalidCodeFence {
+                            line,
+                            info,
+                            message: e.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        Item::Instruction(inst) => {
+            let mut lines = rest_of_paragraph.into_iter().collect::<Vec<String>>();
+            for node in rest {
+                let mut output = vec![];
+                if let Err(e) = format_commonmark(node, &ComrakOptions::default(), &mut output) {
+                    eprintln!("{:?}", e)
+                }
+                lines.push(String::from_utf8_lossy(&output).trim_end().to_string());
+            }
+            let instruction = lines.join("\n\n");
+            if !instruction.trim().is_empty() {
+                inst.instruction = Some(instruction);
+            }
+        }
+        _ => {}
+    }
+    Some(ItemWrap::Item(item))
+}
+
+fn first_list<'a>(node: &'a AstNode<'a>) -> Option<&'a AstNode<'a>> {
+    node.children()
+        .find(|node| matches!(node.data.borrow().value, NodeValue::List(_)))
+}
+
+///
+/// The first line of a paragraph, plus everything after it (if anything)
+///
+fn split_paragraph<'a>(node: &'a AstNode<'a>) -> (String, Option<String>) {
+    let mut first = String::new();
+    let mut rest: Option<String> = None;
+    for child in node.children() {
+        let ast = child.data.borrow();
+        let text = match &ast.value {
+            NodeValue::SoftBreak | NodeValue::LineBreak => {
+                if let Some(rest) = rest.as_mut() {
+                    rest.push('\n');
+                } else {
+                    rest = Some(String::new());
+                }
+                continue;
+            }
+            NodeValue::Text(t) | NodeValue::Code(t) => String::from_utf8_lossy(t),
+            _ => continue,
+        };
+        match rest.as_mut() {
+            Some(rest) => rest.push_str(&text),
+            None => first.push_str(&text),
+        }
+    }
+    (first, rest)
+}
+
+///
+/// The info string, content + line of every fenced code block
+///
+fn fenced_blocks<'a>(nodes: impl Iterator<Item = &'a AstNode<'a>>) -> Vec<(String, String, u32)> {
+    nodes
+        .filter_map(|node| {
+            let ast = node.data.borrow();
+            match &ast.value {
+                NodeValue::CodeBlock(code_block) if code_block.fenced => Some((
+                    String::from_utf8_lossy(&code_block.info).trim().to_string(),
+                    String::from_utf8_lossy(&code_block.literal).to_string(),
+                    ast.start_line,
+                )),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn is_toml(info: &[u8]) -> bool {
+    std::str::from_utf8(info)
+        .map(|info| info.trim() == "toml")
+        .unwrap_or(false)
 }
 
 pub fn debug_ast(asts: &[Ast]) -> impl Debug {
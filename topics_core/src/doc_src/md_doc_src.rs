@@ -1,7 +1,7 @@
 use crate::context::Context;
 use crate::doc::DocResult;
-use crate::doc_err::DocError;
-use crate::doc_src::DocSrcImpl;
+use crate::doc_err::{DocError, Location, LocationError};
+use crate::doc_src::{DocSrcImpl, MdElements, MdProblem};
 use crate::items::Item;
 
 use multi_doc::MultiDoc;
@@ -15,6 +15,9 @@ pub struct MdDocSource {
     pub input_file: Option<PathBuf>,
     pub file_content: String,
     pub doc_src_items: MultiDoc,
+    /// the items of each document in `doc_src_items`, see [`MdDocSource::read_items`]
+    #[serde(skip)]
+    pub items: Vec<Vec<Item>>,
 }
 
 impl DocSrcImpl for MdDocSource {
@@ -30,11 +33,72 @@ impl DocSrcImpl for MdDocSource {
             input_file: Some(pb.clone()),
             file_content: file_str,
             doc_src_items: items,
+            items: vec![],
         };
         Ok(new_self)
     }
 }
 
+impl MdDocSource {
+    ///
+    /// Read the items of every document into `items`, in the same pass that finds
+    /// anything that would stop an item being read. Problems have their lines
+    /// converted back into lines within the whole file
+    ///
+    pub fn read_items(&mut self) -> Vec<DocError> {
+        let mut errors = vec![];
+        let mut items = vec![];
+        for single in &self.doc_src_items.items {
+            let arena = comrak::Arena::new();
+            let (single_items, problems) = MdElements::new(&single.content, &arena).read();
+            for problem in problems {
+                errors.push(self.problem_error(problem, single.line_start));
+            }
+            items.push(single_items);
+        }
+        self.items = items;
+        errors
+    }
+    fn problem_error(&self, problem: MdProblem, line_start: usize) -> DocError {
+        let location_error = |line: u32, description: String| LocationError {
+            location: Some(Location::LineAndCol {
+                line: line as usize + line_start,
+                column: 1,
+            }),
+            input_file: self.input_file.clone(),
+            input_file_src: self.file_content.clone(),
+            description,
+        };
+        match problem {
+            MdProblem::MissingHeading { line, found } => DocError::MissingHeading(location_error(
+                line,
+                format!(
+                    "expected a level 1 heading such as `# Command: Run tests`, but found {}",
+                    found
+                ),
+            )),
+            MdProblem::InvalidCodeFence {
+                line,
+                info,
+                message,
+            } => DocError::InvalidCodeFence(location_error(
+                line,
+                format!("invalid code fence `{}`: {}", info, message),
+            )),
+            MdProblem::MissingPath { line } => DocError::MissingContent(location_error(
+                line,
+                String::from(
+                    "a File Exists Check needs a path in `inline code`, eg: Check that `.npmrc` exists",
+                ),
+            )),
+            MdProblem::InvalidToml { line, message } => DocError::SerdeLocationErr(location_error(
+                line,
+                format!("invalid TOML: {}", message),
+            )),
+        }
+    }
+}
+
 impl FromStr for MdDocSource {
     type Err = DocError;
 
@@ -44,6 +108,7 @@ impl FromStr for MdDocSource {
             input_file: None,
             file_content: s.to_string(),
             doc_src_items: items,
+            items: vec![],
         })
     }
 }
@@ -218,23 +283,8 @@ pub fn parse_inline_kind(input: &str) -> Option<Item> {
 
     // kind + name + other
     if let (Some(first), _maybe_rest) = (lines.get(0), lines.get(1)) {
-        match split_first_line(first).as_mut() {
-            Some(Item::Instruction(inst)) => {
-                return Some(Item::Instruction(inst.clone()));
-            }
-            Some(Item::Command(cmd)) => {
-                return Some(Item::Command(cmd.clone()));
-            }
-            Some(Item::DependencyCheck(dep_check)) => {
-                return Some(Item::DependencyCheck(dep_check.clone()));
-            }
-            Some(Item::Topic(topic)) => {
-                return Some(Item::Topic(topic.clone()));
-            }
-            Some(_v) => {
-                todo!("todo inline list item");
-            }
-            None => {}
+        if let Some(item) = split_first_line(first) {
+            return Some(item);
         }
     }
 
@@ -246,7 +296,7 @@ pub fn parse_inline_kind(input: &str) -> Option<Item> {
     None
 }
 
-fn split_first_line(first_line_input: &str) -> Option<Item> {
+pub(crate) fn split_first_line(first_line_input: &str) -> Option<Item> {
     let split = first_line_input.splitn(2, ':').collect::<Vec<&str>>();
     match (split.get(0), split.get(1)) {
         (Some(kind_str), Some(rest)) => {
@@ -262,9 +312,14 @@ fn split_first_line(first_line_input: &str) -> Option<Item> {
 }
 
 pub mod code_fence {
+    use crate::db::edit_distance;
     use crate::items::{AutoFixInlineArgs, CommandInlineArgs, VerifyInlineArgs};
     use structopt::StructOpt;
 
+    lazy_static::lazy_static! {
+        static ref ANSI: regex::Regex = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+    }
+
     #[derive(Debug, structopt::StructOpt)]
     pub enum Cmd {
         Command(CommandInlineArgs),
@@ -278,17 +333,59 @@ pub mod code_fence {
         pub cmd: Cmd,
     }
 
+    ///
+    /// The names that mark a code block as being meant for `topics`, eg: ```shell verify
+    ///
+    const NAMES: &[&str] = &["command", "verify", "auto-fix", "autofix"];
+
+    ///
+    /// How far a name can be from one of [`NAMES`] before it's no longer treated as a typo
+    ///
+    const MAX_DISTANCE: usize = 2;
+
+    ///
+    /// `Ok(None)` for code blocks that are only documentation, and an error
+    /// for those meant for `topics` whose args are not valid, eg: ```shell verify --unknown
+    ///
+    /// Names that are close to one of [`NAMES`] are errors too, since a typo
+    /// such as ```shell verfy would otherwise leave the code block unused
+    ///
     pub(crate) fn parse_code_fence_args(input: &str) -> anyhow::Result<Option<Cmd>> {
         if let Ok(words) = split_args(&input) {
             if words.len() > 1 {
-                if let Ok(cb) = Cmd::from_iter_safe(&words) {
-                    return Ok(Some(cb));
+                match Cmd::from_iter_safe(&words) {
+                    Ok(cb) => return Ok(Some(cb)),
+                    Err(e) if NAMES.contains(&words[1].as_str()) => {
+                        // the first line is the problem, the rest is usage info
+                        let first = e.message.lines().next().unwrap_or_default();
+                        let message = ANSI.replace_all(first, "");
+                        return Err(anyhow::anyhow!("{}", message.trim_start_matches("error: ")));
+                    }
+                    Err(_) => {
+                        if let Some(name) = near_miss(&words[1]) {
+                            return Err(anyhow::anyhow!(
+                                "unknown name `{}`, did you mean `{}`?",
+                                words[1],
+                                name
+                            ));
+                        }
+                    }
                 }
             }
         }
         Ok(None)
     }
 
+    fn near_miss(word: &str) -> Option<&'static str> {
+        let word = word.to_lowercase();
+        NAMES
+            .iter()
+            .map(|name| (edit_distance(&word, name), *name))
+            .filter(|(distance, _)| *distance <= MAX_DISTANCE)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, name)| name)
+    }
+
     pub(crate) fn split_args(input: &str) -> anyhow::Result<Vec<String>> {
         shellwords::split(input).map_err(|e| e.into())
     }
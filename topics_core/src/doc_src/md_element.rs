@@ -5,10 +5,10 @@ use std::ops::Deref;
 
 use comrak::arena_tree::Node;
 use comrak::nodes::{Ast, AstNode};
-use comrak::{format_html, parse_document, Arena, ComrakOptions};
+use comrak::{format_commonmark, format_html, parse_document, Arena, ComrakOptions};
 
 use crate::doc_src::ast_range::{AstRange, AstRangeImpl};
-use crate::doc_src::{process_node, MdDocSource};
+use crate::doc_src::{process_node, MdDocSource, MdProblem};
 use crate::items::Item;
 use multi_doc::SingleDoc;
 use std::fmt::Debug;
@@ -42,13 +42,6 @@ impl<'a> MdSrc<'a> {
             md_elements: RefCell::new(None),
         }
     }
-    pub fn as_items(&'a self) -> Vec<Item> {
-        self.md_elements
-            .borrow()
-            .as_ref()
-            .expect("unwrap")
-            .as_items()
-    }
     #[cfg(test)]
     pub fn new_from_single(doc_src: &'a MdDocSource) -> Self {
         let a = Arena::new();
@@ -74,6 +67,13 @@ impl<'a> MdSrc<'a> {
         //         .as_items(),
         // );
     }
+    ///
+    /// Line markers are recorded relative to the single document they were parsed from,
+    /// this converts one back into a line number within the whole file
+    ///
+    pub fn line_in_file(&self, line: Option<u32>) -> Option<u32> {
+        line.map(|line| line + self.item_doc.line_start as u32)
+    }
     pub fn range_as_html(&self, range: impl AstRangeImpl) -> String {
         self.md_elements
             .borrow()
@@ -81,6 +81,24 @@ impl<'a> MdSrc<'a> {
             .map(|elements| elements.as_html(range))
             .unwrap_or_default()
     }
+    ///
+    /// Everything in this document except the leading heading, which
+    /// is where the item's kind + name were taken from
+    ///
+    pub fn body_as_html(&self) -> String {
+        self.md_elements
+            .borrow()
+            .as_ref()
+            .map(|elements| elements.as_html(elements.body_range()))
+            .unwrap_or_default()
+    }
+    pub fn range_as_markdown(&self, range: impl AstRangeImpl) -> String {
+        self.md_elements
+            .borrow()
+            .as_ref()
+            .map(|elements| elements.as_markdown(range))
+            .unwrap_or_default()
+    }
     // pub fn items_iter(&'a self) -> impl IntoIterator<Item = &'a Item> {
     //     let borrowed = self.items.borrow();
     //     borrowed.as_ref().iter()
@@ -141,10 +159,19 @@ impl<'a> MdElements<'a> {
 
 impl<'a> MdElements<'a> {
     pub fn as_items<'b>(&self) -> Vec<Item> {
-        let mut path = vec![0];
-        let items = process_node(&self.root, &mut path);
+        let (items, _) = self.read();
         items
     }
+    ///
+    /// Every item, along with anything that stopped an item being read, see [`MdProblem`]
+    ///
+    pub fn read(&self) -> (Vec<Item>, Vec<MdProblem>) {
+        let mut path = vec![0];
+        process_node(&self.root, &mut path)
+    }
+    pub fn body_range(&self) -> AstRange {
+        AstRange::range(&[0, 1], self.root.children().count().saturating_sub(1))
+    }
     pub fn select_ast(&self, range: impl AstRangeImpl) -> Vec<&'a Node<'a, RefCell<Ast>>> {
         let AstRange { ast_len, ast_path } = range.ast_range();
         if ast_path.len() == 1 {
@@ -200,6 +227,33 @@ impl<'a> MdElements<'a> {
             .expect("Valid UTF8 expected")
             .to_string()
     }
+    ///
+    /// Convert an ast range back into markdown
+    ///
+    /// ```rust
+    /// use comrak::Arena;
+    /// use topics_core::doc_src::MdElements;
+    ///
+    /// let input = "# heading\n\nsome *text*";
+    /// let arena = Arena::new();
+    /// let md_elements = MdElements::new(input, &arena);
+    /// let md = md_elements.as_markdown((vec![0, 1], 1));
+    ///
+    /// assert_eq!(md, String::from("some *text*\n"));
+    /// ```
+    pub fn as_markdown(&self, range: impl AstRangeImpl) -> String {
+        let nodes = self.select_ast(range);
+        let mut output = vec![];
+        for node in nodes {
+            let res = format_commonmark(node, &ComrakOptions::default(), &mut output);
+            if let Err(e) = res {
+                eprintln!("{:?}", e)
+            }
+        }
+        std::str::from_utf8(&*output)
+            .expect("Valid UTF8 expected")
+            .to_string()
+    }
 }
 
 impl<'a> TryFrom<&'a MdElements<'a>> for Vec<Item> {
@@ -1,5 +1,7 @@
 pub mod ast_range;
 pub mod doc_src;
+pub mod item_src;
+pub mod json_doc_src;
 pub mod md_comrak;
 pub mod md_doc_src;
 pub mod md_element;
@@ -8,6 +10,8 @@ pub mod yaml_doc_src;
 
 pub use ast_range::*;
 pub use doc_src::*;
+pub use item_src::*;
+pub use json_doc_src::*;
 pub use md_comrak::*;
 pub use md_doc_src::*;
 pub use md_element::*;
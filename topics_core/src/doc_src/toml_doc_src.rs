@@ -2,6 +2,7 @@ use crate::context::Context;
 use crate::doc::{Doc, DocResult};
 use crate::doc_err::{DocError, Location, LocationError};
 use crate::doc_src::DocSrcImpl;
+use crate::items::{Item, KindTagged};
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -27,6 +28,80 @@ impl DocSrcImpl for TomlDocSource {
     }
 }
 
+impl TomlDocSource {
+    ///
+    /// A TOML file can hold either a single item, or many items in an `[[item]]` array
+    ///
+    /// ```toml
+    /// [[item]]
+    /// kind = "Command"
+    /// name = "Run unit tests"
+    /// command = "cargo test"
+    /// ```
+    ///
+    pub fn parse_items(&self, doc: &Doc) -> (Vec<Item>, Vec<DocError>) {
+        match one_or_many_toml(&self.file_content) {
+            Ok(tagged) => {
+                let lines = name_lines(&self.file_content);
+                let items = tagged
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, tagged)| {
+                        let mut item = Item::from(tagged);
+                        if let Some(Some(line)) = lines.get(index) {
+                            item.set_line_start(*line);
+                        }
+                        item
+                    })
+                    .collect();
+                (items, vec![])
+            }
+            Err(toml_err) => (vec![], vec![DocError::from(TomlError { doc, toml_err })]),
+        }
+    }
+}
+
+fn one_or_many_toml(input: &str) -> Result<Vec<KindTagged>, toml::de::Error> {
+    #[derive(Debug, serde::Deserialize)]
+    struct TempItems {
+        item: Vec<KindTagged>,
+    }
+    // parsing into a plain value first means syntax errors are reported before
+    // deciding which shape the document has
+    let value = toml::from_str::<toml::Value>(input)?;
+    if value.get("item").is_some() {
+        toml::from_str::<TempItems>(input).map(|temp| temp.item)
+    } else {
+        toml::from_str::<KindTagged>(input).map(|item| vec![item])
+    }
+}
+
+///
+/// The line of each item's `name`, taken from the spans that the toml parser records.
+/// `None` for items without a name
+///
+fn name_lines(input: &str) -> Vec<Option<u32>> {
+    #[derive(serde::Deserialize)]
+    struct Named {
+        name: Option<toml::Spanned<toml::Value>>,
+    }
+    #[derive(serde::Deserialize)]
+    struct ManyNamed {
+        item: Vec<Named>,
+    }
+    let line = |named: Named| {
+        named
+            .name
+            .map(|name| input[..name.start()].matches('\n').count() as u32 + 1)
+    };
+    match toml::from_str::<ManyNamed>(input) {
+        Ok(many) => many.item.into_iter().map(line).collect(),
+        Err(_) => toml::from_str::<Named>(input)
+            .map(|named| vec![line(named)])
+            .unwrap_or_default(),
+    }
+}
+
 impl FromStr for TomlDocSource {
     type Err = DocError;
 
@@ -61,3 +136,95 @@ impl<'a> From<TomlError<'a>> for DocError {
         DocError::SerdeLocationErr(err)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::context::Context;
+    use crate::doc::Doc;
+    use crate::doc_err::{DocError, Location, LocationError};
+    use crate::doc_src::{DocSource, TomlDocSource};
+    use crate::items::{marker_ref, name_ref, Item};
+    use std::path::PathBuf;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_single_item() -> anyhow::Result<()> {
+        let ctx = Context::from_vec(&[]);
+        let doc = Doc::from_path_buf("../fixtures/toml/single.toml", &ctx)?;
+        assert!(doc.errors.is_empty());
+        assert_eq!(doc.items.len(), 1);
+        match doc.items.get(0) {
+            Some(Item::Command(cmd)) => {
+                assert_eq!(cmd.name.item, "Run unit tests command");
+                assert_eq!(cmd.name.line_start, Some(3));
+            }
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+
+    #[test]
+    fn test_item_array() -> anyhow::Result<()> {
+        let ctx = Context::from_vec(&[]);
+        let doc = Doc::from_path_buf("../fixtures/toml/items.toml", &ctx)?;
+        assert!(doc.errors.is_empty());
+        let names = doc
+            .items
+            .iter()
+            .map(|item| (item.kind_name(), name_ref(item).clone()))
+            .collect::<Vec<(String, String)>>();
+        assert_eq!(
+            names,
+            vec![
+                (String::from("Topic"), String::from("Run unit tests")),
+                (String::from("Command"), String::from("Run unit tests command")),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_location() -> anyhow::Result<()> {
+        let pb = PathBuf::from("/input.toml");
+        let input = "kind = \"Command\"\nname = \"Run unit tests\"\ncommand = \n";
+        let src = TomlDocSource::from_str(input)?;
+        let doc = Doc::from_doc_src(&pb, DocSource::Toml(src), &Default::default())?;
+        assert!(doc.items.is_empty());
+        match doc.errors.get(0) {
+            Some(DocError::SerdeLocationErr(LocationError {
+                location: Some(Location::LineAndCol { line, .. }),
+                ..
+            })) => assert_eq!(*line, 3),
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_name_keys() -> anyhow::Result<()> {
+        let pb = PathBuf::from("/input.toml");
+        let input = r#"[[item]]
+kind = "Command"
+name = "Say hello"
+command = "echo $name"
+
+[item.env.values]
+name = "world"
+
+[[item]]
+kind = "Command"
+name = "Say bye"
+command = "echo bye"
+"#;
+        let src = TomlDocSource::from_str(input)?;
+        let doc = Doc::from_doc_src(&pb, DocSource::Toml(src), &Default::default())?;
+        assert!(doc.errors.is_empty());
+        let lines = doc
+            .items
+            .iter()
+            .map(|item| marker_ref(item).line_start)
+            .collect::<Vec<Option<u32>>>();
+        assert_eq!(lines, vec![Some(3), Some(11)]);
+        Ok(())
+    }
+}
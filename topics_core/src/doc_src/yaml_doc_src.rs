@@ -2,9 +2,12 @@ use crate::context::Context;
 use crate::doc::{Doc, DocResult};
 use crate::doc_err::{DocError, Location, LocationError};
 use crate::doc_src::DocSrcImpl;
+use crate::items::{Item, ItemWrap, KindTagged};
 use multi_doc::{MultiDoc, SingleDoc};
 use std::path::PathBuf;
 use std::str::FromStr;
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
 
 lazy_static::lazy_static! {
     static ref RE: regex::Regex = regex::Regex::new("at line (\\d+)").unwrap();
@@ -35,6 +38,185 @@ impl DocSrcImpl for YamlDocSource {
     }
 }
 
+impl YamlDocSource {
+    ///
+    /// Parse every document in the file into an [`Item`]. A document that
+    /// fails to parse produces an error, but does not prevent the others
+    /// from being used.
+    ///
+    pub fn parse_items(&self, doc: &Doc) -> (Vec<Item>, Vec<DocError>) {
+        let mut items = vec![];
+        let mut errors = vec![];
+        for single_doc in &self.doc_src_items.items {
+            match serde_yaml::from_str::<KindTagged>(&single_doc.content) {
+                Ok(tagged) => items.push(with_yaml_lines(single_doc, Item::from(tagged))),
+                Err(err) => errors.push(from_serde_yaml_error(doc, single_doc, &err)),
+            }
+        }
+        (items, errors)
+    }
+}
+
+///
+/// serde_yaml does not expose positions for values that parsed successfully,
+/// so line markers are taken from a second pass with the underlying parser
+///
+fn with_yaml_lines(single_doc: &SingleDoc, item: Item) -> Item {
+    match Marked::parse(&single_doc.content) {
+        Some(root) => with_lines(item, &root, single_doc.line_start as u32, |node| Some(node)),
+        None => item,
+    }
+}
+
+///
+/// Set the lines of `item` + its deps/steps from `root`, the node it was parsed from.
+/// `fields` finds where an item's own fields are within its node, since the
+/// `kind` + `content` format keeps them under `content`
+///
+pub(crate) fn with_lines(
+    mut item: Item,
+    root: &Marked,
+    offset: u32,
+    fields: fn(&Marked) -> Option<&Marked>,
+) -> Item {
+    let in_file = |node: &Marked| node.line() + offset;
+    let root = match fields(root) {
+        Some(root) => root,
+        None => return item,
+    };
+    if let Some(name) = root.get("name") {
+        item.set_line_start(in_file(marked_string(name)));
+    }
+    let lists: Vec<(&mut Vec<ItemWrap>, &str)> = match &mut item {
+        Item::Topic(topic) => vec![(&mut topic.deps, "deps"), (&mut topic.steps, "steps")],
+        Item::TaskGroup(tg) => vec![(&mut tg.steps, "steps")],
+        _ => vec![],
+    };
+    for (entries, key) in lists {
+        let marks = root.get(key).map(Marked::entries).unwrap_or_default();
+        // entries are matched by position, so a name given in both deps + steps gets both lines
+        for (item_wrap, mark) in entries.iter_mut().zip(marks) {
+            match item_wrap {
+                ItemWrap::NamedRef(marker) => marker.set_line_start(in_file(marked_string(mark))),
+                ItemWrap::Item(inline) => {
+                    inline.set_line_start(in_file(marked_string(inline_name(mark))))
+                }
+            }
+        }
+    }
+    item
+}
+
+///
+/// An inline item is either flat, or wrapped in `content` once as an item
+/// + once more as a dep/step
+///
+fn inline_name(mark: &Marked) -> &Marked {
+    let mut node = mark;
+    while node.get("name").is_none() {
+        match node.get("content") {
+            Some(content) => node = content,
+            None => return mark,
+        }
+    }
+    node.get("name").unwrap_or(mark)
+}
+
+///
+/// Names + named refs are plain strings in hand-written documents, but are
+/// printed as `{ "line_start": .., "item": .. }`, with named refs wrapped once
+/// more in `content`
+///
+fn marked_string(mark: &Marked) -> &Marked {
+    let content = mark.get("content").unwrap_or(mark);
+    content.get("item").unwrap_or(content)
+}
+
+///
+/// A YAML document reduced to its structure + the line (within the document,
+/// starting at 1) where each value begins
+///
+#[derive(Debug)]
+pub(crate) enum Marked {
+    Scalar(String, u32),
+    Seq(Vec<Marked>, u32),
+    Map(Vec<(Marked, Marked)>, u32),
+}
+
+impl Marked {
+    pub(crate) fn parse(content: &str) -> Option<Marked> {
+        let mut builder = MarkedBuilder::default();
+        let mut parser = Parser::new(content.chars());
+        parser.load(&mut builder, false).ok()?;
+        builder.root
+    }
+    pub(crate) fn line(&self) -> u32 {
+        match self {
+            Marked::Scalar(_, line) | Marked::Seq(_, line) | Marked::Map(_, line) => *line,
+        }
+    }
+    pub(crate) fn get(&self, key: &str) -> Option<&Marked> {
+        match self {
+            Marked::Map(entries, _) => entries.iter().find_map(|(k, v)| match k {
+                Marked::Scalar(k, _) if k == key => Some(v),
+                _ => None,
+            }),
+            _ => None,
+        }
+    }
+    pub(crate) fn entries(&self) -> &[Marked] {
+        match self {
+            Marked::Seq(entries, _) => entries,
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Default)]
+struct MarkedBuilder {
+    /// open sequences (`false`) + mappings (`true`), with the values seen so far
+    stack: Vec<(bool, Vec<Marked>, u32)>,
+    root: Option<Marked>,
+}
+
+impl MarkedBuilder {
+    fn push(&mut self, node: Marked) {
+        match self.stack.last_mut() {
+            Some((_, children, _)) => children.push(node),
+            None => self.root = self.root.take().or(Some(node)),
+        }
+    }
+}
+
+impl MarkedEventReceiver for MarkedBuilder {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        let line = mark.line() as u32;
+        match ev {
+            Event::SequenceStart(_) => self.stack.push((false, vec![], line)),
+            Event::MappingStart(_) => self.stack.push((true, vec![], line)),
+            Event::SequenceEnd | Event::MappingEnd => {
+                if let Some((is_map, children, line)) = self.stack.pop() {
+                    let node = if is_map {
+                        let mut pairs = vec![];
+                        let mut children = children.into_iter();
+                        while let (Some(k), Some(v)) = (children.next(), children.next()) {
+                            pairs.push((k, v));
+                        }
+                        Marked::Map(pairs, line)
+                    } else {
+                        Marked::Seq(children, line)
+                    };
+                    self.push(node);
+                }
+            }
+            Event::Scalar(value, ..) => self.push(Marked::Scalar(value, line)),
+            // aliases still take up a place, so that keys + values stay paired
+            Event::Alias(_) => self.push(Marked::Scalar(String::new(), line)),
+            _ => {}
+        }
+    }
+}
+
 impl FromStr for YamlDocSource {
     type Err = DocError;
 
@@ -86,6 +268,7 @@ mod test {
     use crate::context::Context;
     use crate::doc::Doc;
     use crate::doc_src::{DocSource, DocSrcImpl, YamlDocSource};
+    use crate::items::{Item, ItemWrap};
     use std::path::PathBuf;
     use std::str::FromStr;
 
@@ -143,4 +326,61 @@ steps
         insta::assert_debug_snapshot!(doc?.errors);
         Ok(())
     }
+
+    #[test]
+    fn test_lines_from_parser() -> anyhow::Result<()> {
+        let pb = PathBuf::from("/input-yaml.yml");
+        let input = r#"kind: Topic
+"name": Setup
+deps:
+  - install node
+steps:
+  - install node
+  -   "install yarn"
+"#;
+        let srcs = YamlDocSource::from_str(input)?;
+        let doc = Doc::from_doc_src(&pb, DocSource::Yaml(srcs), &Default::default())?;
+        let topic = match doc.items.get(0) {
+            Some(Item::Topic(topic)) => topic,
+            _ => unreachable!(),
+        };
+        let lines = topic
+            .deps
+            .iter()
+            .chain(topic.steps.iter())
+            .map(|item_wrap| match item_wrap {
+                ItemWrap::NamedRef(marker) => marker.line_start,
+                ItemWrap::Item(_) => None,
+            })
+            .collect::<Vec<Option<u32>>>();
+        assert_eq!(topic.name.line_start, Some(2));
+        assert_eq!(lines, vec![Some(4), Some(6), Some(7)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cwd_defaults_to_project_root() -> anyhow::Result<()> {
+        let pb = PathBuf::from("/input-yaml.yml");
+        let input = r#"kind: Command
+name: run tests
+command: cargo test
+---
+kind: FileExistsCheck
+name: has a .env file
+path: .env
+"#;
+        let srcs = YamlDocSource::from_str(input)?;
+        let doc = Doc::from_doc_src(&pb, DocSource::Yaml(srcs), &Default::default())?;
+        let cwds = doc
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::Command(cmd) => cmd.cwd.0.clone(),
+                Item::FileExistsCheck(fec) => fec.cwd.clone(),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<PathBuf>>();
+        assert_eq!(cwds, vec![PathBuf::from("./"), PathBuf::from("./")]);
+        Ok(())
+    }
 }
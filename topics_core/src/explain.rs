@@ -0,0 +1,177 @@
+use crate::db_error::{
+    ConfigRefError, CycleError, DuplicateNameError, ErrCode, MissingRefError, ShellSyntaxError,
+    UnsetVarError,
+};
+
+///
+/// The long-form description of an error code, written as markdown with
+/// a bad example under `## Example` followed by the corrected one under `## Fix`
+///
+#[derive(Debug)]
+pub struct Explanation {
+    pub code: &'static str,
+    pub text: &'static str,
+}
+
+///
+/// Every error code, in order. Graph errors use `E0xx`, problems reading a document use `E1xx`
+///
+pub const EXPLANATIONS: &[Explanation] = &[
+    Explanation {
+        code: CycleError::CODE,
+        text: include_str!("explain/E001.md"),
+    },
+    Explanation {
+        code: MissingRefError::CODE,
+        text: include_str!("explain/E002.md"),
+    },
+    Explanation {
+        code: DuplicateNameError::CODE,
+        text: include_str!("explain/E003.md"),
+    },
+    Explanation {
+        code: ConfigRefError::CODE,
+        text: include_str!("explain/E004.md"),
+    },
+    Explanation {
+        code: ShellSyntaxError::CODE,
+        text: include_str!("explain/E005.md"),
+    },
+    Explanation {
+        code: UnsetVarError::CODE,
+        text: include_str!("explain/E006.md"),
+    },
+    Explanation {
+        code: "E101",
+        text: include_str!("explain/E101.md"),
+    },
+    Explanation {
+        code: "E102",
+        text: include_str!("explain/E102.md"),
+    },
+    Explanation {
+        code: "E103",
+        text: include_str!("explain/E103.md"),
+    },
+    Explanation {
+        code: "E104",
+        text: include_str!("explain/E104.md"),
+    },
+    Explanation {
+        code: "E105",
+        text: include_str!("explain/E105.md"),
+    },
+    Explanation {
+        code: "E106",
+        text: include_str!("explain/E106.md"),
+    },
+    Explanation {
+        code: "E107",
+        text: include_str!("explain/E107.md"),
+    },
+    Explanation {
+        code: "E108",
+        text: include_str!("explain/E108.md"),
+    },
+];
+
+impl Explanation {
+    ///
+    /// The first heading, without the code, eg: `Infinite loop detected`
+    ///
+    pub fn title(&self) -> &'static str {
+        let first = self.text.lines().next().unwrap_or_default();
+        first
+            .find(": ")
+            .map(|index| &first[index + 2..])
+            .unwrap_or(first)
+    }
+}
+
+///
+/// Accepts the code as it's printed in errors (`E001`), without the `E` prefix (`001`)
+/// or without leading zeros (`1`)
+///
+pub fn explain(code: &str) -> Option<&'static Explanation> {
+    let number = code
+        .trim()
+        .trim_start_matches(|c| c == 'E' || c == 'e')
+        .parse::<u32>()
+        .ok()?;
+    let code = format!("E{:03}", number);
+    EXPLANATIONS
+        .iter()
+        .find(|explanation| explanation.code == code)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::doc_err::{DocError, LocationError};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_every_code_is_explained() {
+        let doc_errors = vec![
+            DocError::PathRead {
+                pb: PathBuf::from("a.md"),
+                abs: PathBuf::from("/a.md"),
+                original: std::io::Error::from(std::io::ErrorKind::NotFound),
+            },
+            DocError::SerdeLocationErr(LocationError {
+                location: None,
+                input_file: None,
+                input_file_src: String::new(),
+                description: String::new(),
+            }),
+            DocError::Unknown(String::from("oops")),
+            DocError::NotSupported(PathBuf::from("a.txt")),
+            DocError::MissingHeading(LocationError {
+                location: None,
+                input_file: None,
+                input_file_src: String::new(),
+                description: String::new(),
+            }),
+            DocError::InvalidCodeFence(LocationError {
+                location: None,
+                input_file: None,
+                input_file_src: String::new(),
+                description: String::new(),
+            }),
+            DocError::MissingExtension(PathBuf::from("a")),
+            DocError::MissingContent(LocationError {
+                location: None,
+                input_file: None,
+                input_file_src: String::new(),
+                description: String::new(),
+            }),
+        ];
+        let codes = doc_errors.iter().map(DocError::code).chain(vec![
+            CycleError::CODE,
+            MissingRefError::CODE,
+            DuplicateNameError::CODE,
+            ConfigRefError::CODE,
+            ShellSyntaxError::CODE,
+            UnsetVarError::CODE,
+            "E102",
+        ]);
+        for code in codes {
+            let explanation = explain(code).expect("every code has an explanation");
+            assert!(explanation.text.starts_with(&format!("# {}: ", code)));
+            assert!(explanation.text.contains("\n## Example\n"));
+            assert!(explanation.text.contains("\n## Fix\n"));
+        }
+    }
+
+    #[test]
+    fn test_explain_lookup() {
+        assert_eq!(
+            explain("E001").map(Explanation::title),
+            Some("Infinite loop detected")
+        );
+        assert_eq!(explain("e2").map(|e| e.code), Some("E002"));
+        assert_eq!(explain("104").map(|e| e.code), Some("E104"));
+        assert!(explain("E999").is_none());
+        assert!(explain("cycle").is_none());
+    }
+}
@@ -0,0 +1,367 @@
+use crate::db::{cycle_edges, Db};
+use crate::items::{Item, ItemWrap};
+use crate::run::did_you_mean;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl FromStr for GraphFormat {
+    type Err = GraphFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" | "Dot" | "graphviz" => Ok(GraphFormat::Dot),
+            "mermaid" | "Mermaid" => Ok(GraphFormat::Mermaid),
+            _a => Err(GraphFormatError::Unknown),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GraphFormatError {
+    #[error("graph format not recognised, expected `dot` or `mermaid`")]
+    Unknown,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GraphError {
+    #[error("`{name}` was not found{}", did_you_mean(.suggestions))]
+    RootNotFound {
+        name: String,
+        suggestions: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeKind {
+    Dep,
+    Step,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pub name: String,
+    /// from [`Item::kind_name`], or `Missing` for references that don't resolve
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    pub in_cycle: bool,
+}
+
+///
+/// The dependency graph in a form that's ready to be written out as DOT or Mermaid
+///
+#[derive(Debug, Default)]
+pub struct GraphExport {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+const MISSING: &str = "Missing";
+
+///
+/// Collect every item + the edges between them, in the order they were defined.
+///
+/// When `root` is given, only the items it (transitively) refers to are included
+///
+pub fn graph_export(db: &Db, root: Option<&str>) -> Result<GraphExport, GraphError> {
+    let cycles = cycle_edges(db.graph);
+    let mut output = GraphExport::default();
+    let mut seen: HashSet<String> = HashSet::new();
+    for item in db.all_items() {
+        // when a name is defined more than once, only the definition used for lookups is drawn
+        if !seen.insert(item.name()) {
+            continue;
+        }
+        output.nodes.push(GraphNode {
+            name: item.name(),
+            kind: item.kind_name(),
+        });
+        let (deps, steps): (&[ItemWrap], &[ItemWrap]) = match item {
+            Item::Topic(topic) => (topic.deps.as_slice(), topic.steps.as_slice()),
+            Item::TaskGroup(tg) => (&[][..], tg.steps.as_slice()),
+            _ => continue,
+        };
+        let entries = deps
+            .iter()
+            .map(|entry| (EdgeKind::Dep, entry))
+            .chain(steps.iter().map(|entry| (EdgeKind::Step, entry)));
+        for (kind, entry) in entries {
+            let to = match entry {
+                ItemWrap::NamedRef(line_marker) => line_marker.item.clone(),
+                ItemWrap::Item(inline) => inline.name(),
+            };
+            let in_cycle = cycles
+                .iter()
+                .any(|(from, cycle_to)| **from == item.name() && **cycle_to == to);
+            output.edges.push(GraphEdge {
+                from: item.name(),
+                to,
+                kind,
+                in_cycle,
+            });
+        }
+    }
+
+    // references that don't resolve are still drawn, so that they stand out
+    let missing = output
+        .edges
+        .iter()
+        .filter(|edge| db.get(&edge.to).is_none())
+        .map(|edge| edge.to.clone())
+        .collect::<Vec<String>>();
+    for name in missing {
+        if seen.insert(name.clone()) {
+            output.nodes.push(GraphNode {
+                name,
+                kind: String::from(MISSING),
+            });
+        }
+    }
+
+    match root {
+        Some(root) if db.get(root).is_none() => Err(GraphError::RootNotFound {
+            name: root.to_string(),
+            suggestions: db.suggestions(root),
+        }),
+        Some(root) => Ok(output.reachable_from(root)),
+        None => Ok(output),
+    }
+}
+
+impl GraphExport {
+    fn reachable_from(self, root: &str) -> Self {
+        let mut reachable: HashSet<&str> = HashSet::new();
+        let mut queue = VecDeque::from(vec![root]);
+        while let Some(name) = queue.pop_front() {
+            if !reachable.insert(name) {
+                continue;
+            }
+            for edge in self.edges.iter().filter(|edge| edge.from == name) {
+                queue.push_back(&edge.to);
+            }
+        }
+        Self {
+            nodes: self
+                .nodes
+                .iter()
+                .filter(|node| reachable.contains(node.name.as_str()))
+                .cloned()
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .filter(|edge| reachable.contains(edge.from.as_str()))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    ///
+    /// Graphviz, eg: `topics graph --format dot ./docs/*.md | dot -Tsvg > graph.svg`
+    ///
+    pub fn to_dot(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "digraph topics {{");
+        let _ = writeln!(output, "    rankdir=LR;");
+        for node in &self.nodes {
+            let _ = writeln!(
+                output,
+                "    {} [tooltip={}, {}];",
+                dot_id(&node.name),
+                dot_id(&node.kind),
+                dot_style(&node.kind)
+            );
+        }
+        for edge in &self.edges {
+            let mut attrs = vec![];
+            if edge.kind == EdgeKind::Dep {
+                attrs.push("style=dashed, label=\"dep\"");
+            }
+            if edge.in_cycle {
+                attrs.push("color=red, penwidth=2");
+            }
+            let _ = write!(output, "    {} -> {}", dot_id(&edge.from), dot_id(&edge.to));
+            if !attrs.is_empty() {
+                let _ = write!(output, " [{}]", attrs.join(", "));
+            }
+            let _ = writeln!(output, ";");
+        }
+        let _ = writeln!(output, "}}");
+        output
+    }
+
+    ///
+    /// A Mermaid flowchart, which renders directly in GitHub PRs + most wikis
+    ///
+    pub fn to_mermaid(&self) -> String {
+        let id = |name: &str| {
+            self.nodes
+                .iter()
+                .position(|node| node.name == name)
+                .map(|index| format!("n{}", index))
+                .unwrap_or_default()
+        };
+        let mut output = String::new();
+        let _ = writeln!(output, "flowchart LR");
+        for (index, node) in self.nodes.iter().enumerate() {
+            let (open, close) = mermaid_shape(&node.kind);
+            let label = node.name.replace('"', "#quot;");
+            let _ = writeln!(output, "    n{}{}\"{}\"{}", index, open, label, close);
+        }
+        let mut cycle_links = vec![];
+        for (index, edge) in self.edges.iter().enumerate() {
+            let arrow = match edge.kind {
+                EdgeKind::Dep => "-.->|dep|",
+                EdgeKind::Step => "-->",
+            };
+            let _ = writeln!(output, "    {} {} {}", id(&edge.from), arrow, id(&edge.to));
+            if edge.in_cycle {
+                cycle_links.push(index.to_string());
+            }
+        }
+        let mut kinds = self
+            .nodes
+            .iter()
+            .map(|node| node.kind.as_str())
+            .collect::<Vec<&str>>();
+        kinds.sort_unstable();
+        kinds.dedup();
+        for kind in kinds {
+            let class = mermaid_class(kind);
+            let _ = writeln!(output, "    classDef {} {};", class, mermaid_style(kind));
+            let ids = self
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| node.kind == kind)
+                .map(|(index, _)| format!("n{}", index))
+                .collect::<Vec<String>>();
+            let _ = writeln!(output, "    class {} {};", ids.join(","), class);
+        }
+        if !cycle_links.is_empty() {
+            let _ = writeln!(
+                output,
+                "    linkStyle {} stroke:red,stroke-width:2px;",
+                cycle_links.join(",")
+            );
+        }
+        output
+    }
+}
+
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn dot_style(kind: &str) -> &'static str {
+    match kind {
+        "Topic" => "shape=box, style=\"rounded,bold\"",
+        "Task Group" => "shape=box, style=rounded",
+        "Command" => "shape=box",
+        "Instruction" => "shape=note",
+        "Config" => "shape=cylinder",
+        MISSING => "shape=box, style=dashed, color=red",
+        // every kind of check
+        _ => "shape=diamond",
+    }
+}
+
+fn mermaid_shape(kind: &str) -> (&'static str, &'static str) {
+    match kind {
+        "Topic" | "Task Group" => ("([", "])"),
+        "Instruction" => (">", "]"),
+        "Config" => ("[(", ")]"),
+        "Command" | MISSING => ("[", "]"),
+        _ => ("{", "}"),
+    }
+}
+
+/// Mermaid class names can't contain spaces
+fn mermaid_class(kind: &str) -> String {
+    kind.replace(' ', "")
+}
+
+fn mermaid_style(kind: &str) -> &'static str {
+    match kind {
+        "Topic" | "Task Group" => "fill:#dbeafe,stroke:#1d4ed8",
+        "Command" => "fill:#f3f4f6,stroke:#374151",
+        "Instruction" => "fill:#fef9c3,stroke:#a16207",
+        "Config" => "fill:#ede9fe,stroke:#6d28d9",
+        MISSING => "fill:#fee2e2,stroke:#b91c1c,stroke-dasharray:4",
+        _ => "fill:#dcfce7,stroke:#15803d",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::Context;
+    use crate::db::with_db;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_mermaid_deps_and_steps() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/markdown/handbook.md")]);
+        let mermaid = with_db(&f, |db, _errors| {
+            graph_export(db, None).expect("graph").to_mermaid()
+        });
+        let expected = r#"flowchart LR
+    n0(["Run the app"])
+    n1>"Read the README"]
+    n2["start the server"]
+    n0 -.->|dep| n1
+    n0 --> n2
+    classDef Command fill:#f3f4f6,stroke:#374151;
+    class n2 Command;
+    classDef Instruction fill:#fef9c3,stroke:#a16207;
+    class n1 Instruction;
+    classDef Topic fill:#dbeafe,stroke:#1d4ed8;
+    class n0 Topic;
+"#;
+        assert_eq!(mermaid, expected);
+    }
+
+    #[test]
+    fn test_dot_highlights_cycles() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/cycle/topics.md")]);
+        let dot = with_db(&f, |db, _errors| {
+            graph_export(db, Some("Build the app"))
+                .expect("graph")
+                .to_dot()
+        });
+        assert!(dot.contains("\"Build the app\" -> \"Run the app\" [color=red, penwidth=2];"));
+        assert!(dot.contains(
+            "\"Run the app\" -> \"Install dependencies\" [style=dashed, label=\"dep\", color=red, penwidth=2];"
+        ));
+    }
+
+    #[test]
+    fn test_root_not_found() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/cycle/topics.md")]);
+        let message = with_db(&f, |db, _errors| {
+            match graph_export(db, Some("Build teh app")) {
+                Err(err) => err.to_string(),
+                Ok(_) => unreachable!(),
+            }
+        });
+        assert_eq!(
+            message,
+            "`Build teh app` was not found, did you mean `Build the app`?"
+        );
+    }
+}
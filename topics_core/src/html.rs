@@ -1,17 +1,22 @@
 use crate::context::Context;
-use crate::doc::Doc;
-use crate::doc_src::MdSrc;
+use crate::db::{config_lookup, split_warnings};
+use crate::db_error::SerializedError;
+use crate::doc_src::ItemSrc;
 use crate::html_template::HtmlTemplate;
-use crate::items::{Item, LineMarker};
-use crate::print::Print;
-use crate::{DbError, ErrorRef, Output, SerializedError};
+use crate::items::{Item, ItemWrap};
+use crate::markdown::{slug, unique_slug};
+use crate::DbError;
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::path::PathBuf;
 
 #[derive(Debug)]
 pub struct HtmlOutput {
     pub pages: Vec<HtmlPage>,
     pub assets: Vec<Asset>,
+    /// problems in the graph, nothing should be written when there are any
+    pub errors: Vec<SerializedError>,
+    pub warnings: Vec<SerializedError>,
 }
 
 impl Default for HtmlOutput {
@@ -28,6 +33,8 @@ impl Default for HtmlOutput {
                     content: Some(JS_FILE.into()),
                 },
             ],
+            errors: vec![],
+            warnings: vec![],
         }
     }
 }
@@ -37,6 +44,7 @@ pub struct HtmlPage {
     pub pb: PathBuf,
     pub content: Option<String>,
     pub title: Option<String>,
+    pub sidebar: Option<String>,
 }
 
 #[derive(Debug)]
@@ -48,13 +56,23 @@ pub struct Asset {
 const HTML_PAGE: &str = include_str!("../../web/index.html");
 const CSS_FILE: &str = include_str!("../../web/css/all.css");
 const JS_FILE: &str = include_str!("../../web/js/all.js");
+const H1_TEMPLATE: &str = include_str!("../../web/templates/h1.html");
+const CODE_TEMPLATE: &str = include_str!("../../web/templates/code.html");
+const LINK_TEMPLATE: &str = include_str!("../../web/templates/link.html");
 
 impl HtmlTemplate for HtmlPage {
-    fn template(&self, ctx: &Context) -> anyhow::Result<String> {
-        Ok(HTML_PAGE
-            .replace("{{title}}", "oops!")
-            .replace("{{content}}", "<h1>MY page</h1>")
-            .to_string())
+    fn template(&self, _ctx: &Context) -> anyhow::Result<String> {
+        // placeholders are filled in a single pass, so a value that happens to
+        // contain `{{content}}` etc is never substituted again
+        let title = escape(self.title.as_deref().unwrap_or("Topics"));
+        Ok(fill(
+            HTML_PAGE,
+            &[
+                ("{{title}}", &title),
+                ("{{sidebar}}", self.sidebar.as_deref().unwrap_or_default()),
+                ("{{content}}", self.content.as_deref().unwrap_or_default()),
+            ],
+        ))
     }
 }
 
@@ -64,6 +82,7 @@ impl HtmlPage {
             pb: pb.into(),
             content: None,
             title: None,
+            sidebar: None,
         }
     }
     pub fn title(mut self, title: impl Into<String>) -> Self {
@@ -74,40 +93,308 @@ impl HtmlPage {
         self.content = Some(content.into());
         self
     }
+    pub fn sidebar(mut self, sidebar: impl Into<String>) -> Self {
+        self.sidebar = Some(sidebar.into());
+        self
+    }
     pub fn finish(self) -> Self {
         Self { ..self }
     }
 }
 
+///
+/// One page per named item, plus an index of every Topic.
+///
+/// Topic pages link each of their dependencies + steps to the page of the item
+/// being referenced. Items from markdown documents render their original markdown.
+///
 pub fn output_html<'a>(
-    graph: &'a HashMap<&'a String, Vec<&'a LineMarker<String>>>,
-    lookup: &'a HashMap<&'a String, (&'a MdSrc<'a>, &'a Item)>,
-    items: &'a Vec<(&'_ MdSrc, Vec<Item>)>,
+    lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
+    items: &'a Vec<(ItemSrc<'_>, Vec<Item>)>,
+    errors: Vec<DbError<'a>>,
 ) -> HtmlOutput {
-    // let mut output = Output::default();
-    // let cycles = detect_cycle(graph, lookup);
-    // for c in cycles {
-    //     match c {
-    //         DbError::Cycle(ErrorRef { inner, .. }) => {
-    //             output.errors.push(SerializedError::Cycle(inner));
-    //         }
-    //     }
-    // }
-    // for (md_src, items) in items {
-    //     if let Some(pb) = &md_src.md_doc_src.input_file {
-    //         let pb = pb.clone();
-    //         output.docs.entry(pb).or_insert(md_src.md_doc_src.clone());
-    //     }
-    //     for item in items {
-    //         output.items.push(item.clone()); // todo: How to remove this clone...
-    //     }
-    // }
-    // output
-    let p1 = HtmlPage::new("index.html")
-        .title("Topic: hello!")
-        .content("<!--Hello-->")
-        .finish();
-    let mut output = HtmlOutput::default();
-    output.pages.push(p1);
+    let (errors, warnings) = split_warnings(errors);
+    let mut output = HtmlOutput {
+        errors,
+        warnings,
+        ..Default::default()
+    };
+
+    // only top-level items get a page, and names whose slugs collide get a suffix.
+    // A name defined more than once is one of `errors`, so those pages are never written
+    let mut slugs = HashMap::new();
+    slugs.insert(String::from("index"), 1);
+    let mut pages: Vec<(ItemSrc, &Item, PathBuf)> = vec![];
+    let mut page_paths: HashMap<String, PathBuf> = HashMap::new();
+    for (src, items) in items {
+        for item in items {
+            let name = item.name();
+            if page_paths.contains_key(&name) {
+                continue;
+            }
+            let pb = PathBuf::from(format!("{}.html", unique_slug(&name, &mut slugs)));
+            page_paths.insert(name, pb.clone());
+            pages.push((*src, item, pb));
+        }
+    }
+
+    let sidebar = pages
+        .iter()
+        .filter(|(_, item, _)| matches!(item, Item::Topic(_)))
+        .map(|(_, item, pb)| link(pb, &item.name()))
+        .collect::<String>();
+
+    let mut index = h1("Topics");
+    let _ = writeln!(index, "<ul>{}</ul>", sidebar);
+    output.pages.push(
+        HtmlPage::new("index.html")
+            .title("Topics")
+            .content(index)
+            .sidebar(&sidebar)
+            .finish(),
+    );
+
+    for (src, item, pb) in pages {
+        let content = item_html(item, src, lookup, &page_paths);
+        output.pages.push(
+            HtmlPage::new(pb)
+                .title(format!("{}: {}", item.kind_name(), item.name()))
+                .content(content)
+                .sidebar(&sidebar)
+                .finish(),
+        );
+    }
     output
 }
+
+fn item_html<'a>(
+    item: &Item,
+    src: ItemSrc<'_>,
+    lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
+    page_paths: &HashMap<String, PathBuf>,
+) -> String {
+    let mut output = h1(&item.name());
+    match (item, src.md()) {
+        (Item::Topic(topic), _) => {
+            write_list(&mut output, "Dependencies", &topic.deps, page_paths);
+            write_list(&mut output, "Steps", &topic.steps, page_paths);
+        }
+        (Item::TaskGroup(tg), _) => {
+            write_list(&mut output, "Steps", &tg.steps, page_paths);
+        }
+        (_, Some(md_src)) => {
+            output.push_str(&md_src.body_as_html());
+        }
+        (Item::Command(cmd), None) => {
+            let _ = writeln!(
+                output,
+                "<p>directory: <code>{}</code></p>",
+                escape(&cmd.cwd.to_string())
+            );
+            output.push_str(&code(&cmd.interpolated(config_lookup(lookup))));
+        }
+        (Item::DependencyCheck(dc), None) => {
+            if let Some(url) = &dc.url {
+                let _ = writeln!(
+                    output,
+                    "<p><a href=\"{url}\">{url}</a></p>",
+                    url = escape(url)
+                );
+            }
+            output.push_str(&code(&dc.verify));
+            if let Some(autofix) = &dc.autofix {
+                let _ = writeln!(output, "<p>To fix:</p>");
+                output.push_str(&code(autofix));
+            }
+        }
+        (Item::Instruction(inst), None) => {
+            if let Some(instruction) = &inst.instruction {
+                let _ = writeln!(output, "<p>{}</p>", escape(instruction));
+            }
+        }
+        (Item::FileExistsCheck(fec), None) => {
+            let _ = writeln!(
+                output,
+                "<p>file: <code>{}</code></p>",
+                escape(&fec.cwd.join(&fec.path).display().to_string())
+            );
+        }
+        (Item::Config(config), None) => {
+            let mut values = config.values.iter().collect::<Vec<(&String, &String)>>();
+            values.sort();
+            let _ = write!(output, "<ul>");
+            for (key, value) in values {
+                let _ = write!(
+                    output,
+                    "<li><code>{}</code>: <code>{}</code></li>",
+                    escape(key),
+                    escape(value)
+                );
+            }
+            let _ = writeln!(output, "</ul>");
+        }
+        (Item::HostEntriesCheck(hec), None) => {
+            let _ = write!(output, "<ul>");
+            for entry in &hec.hosts {
+                let _ = write!(
+                    output,
+                    "<li><code>127.0.0.1  {}</code></li>",
+                    escape(&entry.domain)
+                );
+            }
+            let _ = writeln!(output, "</ul>");
+        }
+    }
+    output
+}
+
+fn write_list(
+    output: &mut String,
+    title: &str,
+    entries: &[ItemWrap],
+    page_paths: &HashMap<String, PathBuf>,
+) {
+    if entries.is_empty() {
+        return;
+    }
+    let _ = write!(output, "<h2>{}</h2><ul>", title);
+    for entry in entries {
+        match entry {
+            ItemWrap::NamedRef(line_marker) => match page_paths.get(&line_marker.item) {
+                Some(pb) => output.push_str(&link(pb, &line_marker.item)),
+                // missing references are reported elsewhere, and inline items
+                // have no page of their own, so neither gets a link
+                None => {
+                    let _ = write!(output, "<li>{}</li>", escape(&line_marker.item));
+                }
+            },
+            ItemWrap::Item(item) => {
+                let _ = write!(output, "<li>{}</li>", escape(&item.name()));
+            }
+        }
+    }
+    let _ = writeln!(output, "</ul>");
+}
+
+fn h1(content: &str) -> String {
+    H1_TEMPLATE
+        .replace("{{id}}", &slug(content))
+        .replace("{{content}}", &escape(content))
+}
+
+fn code(content: &str) -> String {
+    CODE_TEMPLATE
+        .replace("{{lang}}", "sh")
+        .replace("{{content}}", &escape(content.trim_end()))
+}
+
+fn link(href: &PathBuf, content: &str) -> String {
+    LINK_TEMPLATE
+        .replace("{{href}}", &href.display().to_string())
+        .replace("{{content}}", &escape(content))
+        .trim_end()
+        .to_string()
+}
+
+///
+/// Replace each of the `(placeholder, value)` pairs, scanning the template only once
+///
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some((index, (placeholder, value))) = values
+        .iter()
+        .filter_map(|pair| rest.find(pair.0).map(|index| (index, pair)))
+        .min_by_key(|(index, _)| *index)
+    {
+        output.push_str(&rest[..index]);
+        output.push_str(value);
+        rest = &rest[index + placeholder.len()..];
+    }
+    output.push_str(rest);
+    output
+}
+
+fn escape(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::try_from_docs;
+    use crate::print::OutputKind;
+    use crate::Outputs;
+
+    fn html_output(paths: &[&str]) -> HtmlOutput {
+        let ctx = Context::default();
+        let paths = paths.iter().map(PathBuf::from).collect::<Vec<PathBuf>>();
+        let f = ctx.read_docs_unwrapped(&paths);
+        match try_from_docs(&f, &OutputKind::Html) {
+            Ok(Outputs::Html(output)) => output,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_html_pages() {
+        let output = html_output(&["../fixtures/markdown/handbook.md"]);
+        let paths = output
+            .pages
+            .iter()
+            .map(|page| page.pb.clone())
+            .collect::<Vec<PathBuf>>();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("index.html"),
+                PathBuf::from("run-the-app.html"),
+                PathBuf::from("read-the-readme.html"),
+                PathBuf::from("start-the-server.html"),
+            ]
+        );
+        let topic = output.pages[1].content.as_deref().unwrap_or_default();
+        assert!(topic.contains(
+            r#"<li><a href="read-the-readme.html" class="sidebar-link">Read the README</a></li>"#
+        ));
+        let instruction = output.pages[2].content.as_deref().unwrap_or_default();
+        assert!(instruction.contains("<p>Please read the <strong>README</strong> first.</p>"));
+        assert!(output.errors.is_empty());
+    }
+
+    #[test]
+    fn test_html_errors() {
+        let output = html_output(&[
+            "../fixtures/duplicate/team_a.md",
+            "../fixtures/duplicate/team_b.md",
+        ]);
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.errors[0].code(), "E003");
+    }
+
+    #[test]
+    fn test_template_escapes_title() {
+        let page = HtmlPage::new("index.html")
+            .title("<Topics>")
+            .content("<p>hello</p>")
+            .finish();
+        let html = page.template(&Context::default()).expect("template");
+        assert!(html.contains("<title>&lt;Topics&gt;</title>"));
+        assert!(html.contains("<p>hello</p>"));
+    }
+
+    #[test]
+    fn test_template_placeholders_in_values() {
+        let page = HtmlPage::new("index.html")
+            .sidebar("<li>{{content}}</li>")
+            .content("<p>{{sidebar}}</p>")
+            .finish();
+        let html = page.template(&Context::default()).expect("template");
+        assert!(html.contains("<li>{{content}}</li>"));
+        assert!(html.contains("<p>{{sidebar}}</p>"));
+    }
+}
@@ -1,21 +1,25 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use std::str::FromStr;
 
 use crate::cwd::Cwd;
 use crate::doc_src::ast_range::AstRange;
 use crate::doc_src::code_fence;
-use crate::items::LineMarker;
+use crate::items::{Config, LineMarker};
 use typescript_definitions::TypeScriptify;
 
-#[derive(Debug, Clone, serde::Serialize, TypeScriptify)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, TypeScriptify)]
 pub struct Command {
     pub name: LineMarker<String>,
+    #[serde(default = "Cwd::project_root")]
     pub cwd: Cwd,
     pub command: String,
     pub env: Option<Env>,
     #[serde(skip)]
     pub ast_range: AstRange,
+    /// the line (within its document) of the markdown code block `command` was read from
+    #[serde(skip)]
+    pub command_line: Option<u32>,
 }
 
 #[derive(Debug, structopt::StructOpt)]
@@ -24,44 +28,134 @@ pub struct CommandInlineArgs {
     pub cwd: Cwd,
 }
 
-#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, TypeScriptify)]
+#[derive(
+    Debug, Clone, PartialEq, Default, serde::Deserialize, serde::Serialize, TypeScriptify,
+)]
 pub struct Env {
     pub values: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub values_from: Vec<ValuesFrom>,
+}
+
+///
+/// Read every value under `path` from the Config named `config`. Each one
+/// becomes an environment variable named `prefix` + the rest of its path
+///
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize, TypeScriptify)]
+pub struct ValuesFrom {
+    pub prefix: String,
+    pub config: String,
+    pub path: String,
 }
 
+///
+/// Parse the TOML code block that sits alongside a command in markdown
+///
+/// ```toml
+/// NODE_ENV = "production"
+/// DOCKER_CLIENT_ = { valuesFrom = "global-vars", path = "images.client" }
+/// ```
+///
 impl FromStr for Env {
     type Err = anyhow::Error;
 
-    fn from_str(_: &str) -> Result<Self, Self::Err> {
-        Ok(Self { values: None })
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Entry {
+            Value(String),
+            From {
+                #[serde(rename = "valuesFrom")]
+                values_from: String,
+                path: String,
+            },
+        }
+        let mut env = Env::default();
+        for (key, entry) in toml::from_str::<BTreeMap<String, Entry>>(s)? {
+            match entry {
+                Entry::Value(value) => {
+                    env.values
+                        .get_or_insert_with(HashMap::new)
+                        .insert(key, value);
+                }
+                Entry::From { values_from, path } => env.values_from.push(ValuesFrom {
+                    prefix: key,
+                    config: values_from,
+                    path,
+                }),
+            }
+        }
+        Ok(env)
+    }
+}
+
+impl Env {
+    ///
+    /// Every environment variable, including those read from Configs.
+    ///
+    /// Configs or paths that can't be found are skipped, they are reported as graph errors instead
+    ///
+    pub fn resolve<'a>(
+        &self,
+        config: impl Fn(&str) -> Option<&'a Config>,
+    ) -> HashMap<String, String> {
+        let mut output = self.values.clone().unwrap_or_default();
+        for values_from in &self.values_from {
+            let values = config(&values_from.config).and_then(|c| c.values_at(&values_from.path));
+            for (key, value) in values.unwrap_or_default() {
+                output.insert(format!("{}{}", values_from.prefix, key), value);
+            }
+        }
+        output
     }
 }
 
 impl Default for Command {
     fn default() -> Self {
         Self {
-            cwd: Default::default(),
+            cwd: Cwd::project_root(),
             command: "echo 'no command'; exit 1; ".to_string(),
             name: LineMarker::new(String::new(), None),
             env: Default::default(),
             ast_range: Default::default(),
+            command_line: None,
         }
     }
 }
 
 impl Command {
-    pub fn with_content(&mut self, content: &str) {
+    pub fn env_values<'a>(
+        &self,
+        config: impl Fn(&str) -> Option<&'a Config>,
+    ) -> HashMap<String, String> {
+        self.env
+            .as_ref()
+            .map(|env| env.resolve(config))
+            .unwrap_or_default()
+    }
+    ///
+    /// The command, with every `${NAME}` replaced by the value from [`Command::env_values`].
+    /// Unknown names are left in place, since they may come from the environment at run time
+    ///
+    pub fn interpolated<'a>(&self, config: impl Fn(&str) -> Option<&'a Config>) -> String {
+        self.env_values(config)
+            .iter()
+            .fold(self.command.clone(), |command, (key, value)| {
+                command.replace(&format!("${{{}}}", key), value)
+            })
+    }
+    pub fn with_content(&mut self, content: &str, line: u32) {
         self.command = content.to_string();
+        self.command_line = Some(line);
     }
-    pub fn with_cli_params(&mut self, params: &str) {
-        match code_fence::parse_code_fence_args(params) {
-            Ok(Some(code_fence::Cmd::Command(inner))) => {
-                // we only assign this code block if it has ```shell command ...
-                self.cwd = inner.cwd;
-            }
-            _a => {
-                // todo!("handle parsing code-block inline args")
-            }
+    ///
+    /// Errors when the code block is meant for `topics`, but its args are not valid
+    ///
+    pub fn with_cli_params(&mut self, params: &str) -> anyhow::Result<()> {
+        if let Some(code_fence::Cmd::Command(inner)) = code_fence::parse_code_fence_args(params)? {
+            // we only assign this code block if it has ```shell command ...
+            self.cwd = inner.cwd;
         }
+        Ok(())
     }
 }
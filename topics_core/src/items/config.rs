@@ -0,0 +1,124 @@
+use crate::items::LineMarker;
+use std::collections::HashMap;
+use typescript_definitions::TypeScriptify;
+
+///
+/// A named set of values that Commands can read from through their [`Env`](crate::items::Env)
+///
+/// Nested values are flattened into dot-separated paths, so that
+///
+/// ```toml
+/// images.client = { tag = "nbs-frontend" }
+/// ```
+///
+/// is stored as `images.client.tag = "nbs-frontend"`
+///
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize, TypeScriptify)]
+pub struct Config {
+    pub name: LineMarker<String>,
+    #[serde(default, alias = "data", deserialize_with = "flatten_values")]
+    pub values: HashMap<String, String>,
+}
+
+impl Config {
+    pub fn with_toml(&mut self, input: &str) -> anyhow::Result<()> {
+        let value = toml::from_str::<serde_json::Value>(input)?;
+        flatten("", &value, &mut self.values);
+        Ok(())
+    }
+    ///
+    /// Every value at, or underneath `path`. Keys are whatever remains of each path
+    /// after `path` itself, so the value at exactly `path` has an empty key
+    ///
+    pub fn values_at(&self, path: &str) -> Option<Vec<(String, String)>> {
+        if let Some(value) = self.values.get(path) {
+            return Some(vec![(String::new(), value.clone())]);
+        }
+        let prefix = format!("{}.", path);
+        let mut values = self
+            .values
+            .iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix(&prefix)
+                    .map(|rest| (rest.to_string(), value.clone()))
+            })
+            .collect::<Vec<(String, String)>>();
+        values.sort();
+        if values.is_empty() {
+            None
+        } else {
+            Some(values)
+        }
+    }
+}
+
+fn flatten_values<'de, D>(deserializer: D) -> Result<HashMap<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = <serde_json::Value as serde::Deserialize>::deserialize(deserializer)?;
+    let mut values = HashMap::new();
+    flatten("", &value, &mut values);
+    Ok(values)
+}
+
+fn flatten(path: &str, value: &serde_json::Value, output: &mut HashMap<String, String>) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                flatten(&join(key), value, output);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                flatten(&join(&index.to_string()), value, output);
+            }
+        }
+        serde_json::Value::String(string) => {
+            output.insert(path.to_string(), string.clone());
+        }
+        serde_json::Value::Null => {}
+        other => {
+            output.insert(path.to_string(), other.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_values_at() -> anyhow::Result<()> {
+        let mut config = Config::default();
+        config.with_toml(
+            r#"
+images.client = { tag = "nbs-frontend", context = "./containers/www/client" }
+images.graph = { tag = "nbs-graph", port = 8080 }
+"#,
+        )?;
+        assert_eq!(
+            config.values_at("images.client"),
+            Some(vec![
+                (
+                    String::from("context"),
+                    String::from("./containers/www/client")
+                ),
+                (String::from("tag"), String::from("nbs-frontend")),
+            ])
+        );
+        assert_eq!(
+            config.values_at("images.graph.port"),
+            Some(vec![(String::new(), String::from("8080"))])
+        );
+        assert_eq!(config.values_at("images.clint"), None);
+        Ok(())
+    }
+}
@@ -3,11 +3,20 @@ use crate::doc_src::code_fence;
 use crate::items::LineMarker;
 use typescript_definitions::TypeScriptify;
 
-#[derive(Debug, Clone, serde::Serialize, TypeScriptify)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, TypeScriptify)]
 pub struct DependencyCheck {
     pub name: LineMarker<String>,
     pub verify: String,
+    /// where `verify` runs, relative to the project, from the `--cwd` of its code fence
+    #[serde(default = "Cwd::project_root")]
+    pub verify_cwd: Cwd,
+    /// the line (within its document) of the markdown code block `verify` was read from
+    #[serde(skip)]
+    pub verify_line: Option<u32>,
     pub autofix: Option<String>,
+    /// where `autofix` runs, see `verify_cwd`
+    #[serde(default = "Cwd::project_root")]
+    pub autofix_cwd: Cwd,
     pub url: Option<String>,
 }
 
@@ -16,7 +25,10 @@ impl DependencyCheck {
         Self {
             verify: name.to_string(),
             name: LineMarker::new(verify.to_string(), None),
+            verify_cwd: Cwd::project_root(),
+            verify_line: None,
             autofix: None,
+            autofix_cwd: Cwd::project_root(),
             url: None,
         }
     }
@@ -27,25 +39,35 @@ impl Default for DependencyCheck {
         Self {
             verify: "echo 'verify script missing'; exit 1;".to_string(),
             name: LineMarker::new("install node".to_string(), None),
+            verify_cwd: Cwd::project_root(),
+            verify_line: None,
             autofix: None,
+            autofix_cwd: Cwd::project_root(),
             url: Some("https://nodejs.org".to_string()),
         }
     }
 }
 
 impl DependencyCheck {
-    pub fn with_content(&mut self, content: &str, params: &str) {
-        match code_fence::parse_code_fence_args(params) {
-            Ok(Some(code_fence::Cmd::Verify(_))) => {
+    ///
+    /// Errors when the code block is meant to be a script, but its args are not valid
+    ///
+    pub fn with_content(&mut self, content: &str, params: &str, line: u32) -> anyhow::Result<()> {
+        match code_fence::parse_code_fence_args(params)? {
+            Some(code_fence::Cmd::Verify(args)) => {
                 self.verify = content.to_string();
+                self.verify_cwd = args.cwd;
+                self.verify_line = Some(line);
             }
-            Ok(Some(code_fence::Cmd::AutoFix(_))) => {
+            Some(code_fence::Cmd::AutoFix(args)) => {
                 self.autofix = Some(content.to_string());
+                self.autofix_cwd = args.cwd;
             }
             _a => {
-                todo!("what to do when inline args are invalid")
+                // any other code block is just part of the documentation
             }
         }
+        Ok(())
     }
 }
 
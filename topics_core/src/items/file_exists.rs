@@ -1,19 +1,26 @@
+use crate::cwd::Cwd;
+use crate::items::LineMarker;
 use std::path::PathBuf;
 use typescript_definitions::TypeScriptify;
 
 #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize, TypeScriptify)]
 pub struct FileExistsCheck {
+    #[serde(default = "project_root")]
     pub cwd: PathBuf,
     pub path: PathBuf,
-    pub name: String,
+    pub name: LineMarker<String>,
+}
+
+fn project_root() -> PathBuf {
+    Cwd::project_root().0
 }
 
 impl Default for FileExistsCheck {
     fn default() -> Self {
         Self {
-            cwd: PathBuf::from("./"),
-            path: PathBuf::from("Cargo.toml"),
-            name: "Cargo.toml exists".to_string(),
+            cwd: project_root(),
+            path: PathBuf::new(),
+            name: LineMarker::new(String::new(), None),
         }
     }
 }
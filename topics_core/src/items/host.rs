@@ -1,12 +1,54 @@
+use crate::items::LineMarker;
 use typescript_definitions::TypeScriptify;
 
-#[derive(Debug, Clone, PartialEq, serde::Serialize, TypeScriptify)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize, TypeScriptify)]
 pub struct HostEntriesCheck {
     pub hosts: Vec<HostEntry>,
-    pub name: String,
+    pub name: LineMarker<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, serde::Serialize, TypeScriptify)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize, TypeScriptify)]
 pub struct HostEntry {
     pub domain: String,
 }
+
+impl Default for HostEntriesCheck {
+    fn default() -> Self {
+        Self {
+            hosts: vec![],
+            name: LineMarker::new(String::new(), None),
+        }
+    }
+}
+
+/// Where host entries are checked
+pub const HOSTS_FILE: &str = "/etc/hosts";
+
+impl HostEntriesCheck {
+    ///
+    /// Every entry whose domain is not listed in `hosts_file`, which is read
+    /// as the contents of an `/etc/hosts` file.
+    ///
+    /// ```rust
+    /// # use topics_core::items::{HostEntriesCheck, HostEntry};
+    /// let hec = HostEntriesCheck {
+    ///     hosts: vec![HostEntry { domain: "a.test".into() }, HostEntry { domain: "b.test".into() }],
+    ///     ..Default::default()
+    /// };
+    /// let missing = hec.missing("127.0.0.1  localhost a.test\n# 127.0.0.1  b.test\n");
+    /// assert_eq!(missing, vec!["b.test"]);
+    /// ```
+    ///
+    pub fn missing(&self, hosts_file: &str) -> Vec<&str> {
+        let listed = hosts_file
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default())
+            .flat_map(|line| line.split_whitespace().skip(1))
+            .collect::<Vec<&str>>();
+        self.hosts
+            .iter()
+            .map(|entry| entry.domain.as_str())
+            .filter(|domain| !listed.contains(domain))
+            .collect()
+    }
+}
@@ -5,9 +5,12 @@ use crate::doc_src::ast_range::AstRange;
 use crate::items::LineMarker;
 use typescript_definitions::TypeScriptify;
 
-#[derive(Debug, Clone, serde::Serialize, TypeScriptify)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, TypeScriptify)]
 pub struct Instruction {
     pub name: LineMarker<String>,
+    /// Plain text instructions, as given in YAML/TOML/JSON documents.
+    /// Markdown instructions are kept in the original AST instead, see `ast_range`
+    pub instruction: Option<String>,
     #[serde(skip)]
     pub ast_range: AstRange,
 }
@@ -16,6 +19,7 @@ impl Default for Instruction {
     fn default() -> Self {
         Self {
             ast_range: AstRange::default(),
+            instruction: None,
             name: LineMarker::new(String::new(), None),
         }
     }
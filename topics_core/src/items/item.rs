@@ -1,11 +1,11 @@
-use crate::items::{Command, Instruction};
+use crate::items::{Command, Config, Instruction};
 use crate::items::{DependencyCheck, TaskGroup};
 use crate::items::{FileExistsCheck, LineMarker};
 use crate::items::{HostEntriesCheck, Topic};
 use std::str::FromStr;
 use typescript_definitions::TypeScriptify;
 
-#[derive(Debug, Clone, serde::Serialize, TypeScriptify)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, TypeScriptify)]
 #[serde(tag = "kind", content = "content")]
 pub enum Item {
     Command(Command),
@@ -15,6 +15,7 @@ pub enum Item {
     HostEntriesCheck(HostEntriesCheck),
     Topic(Topic),
     TaskGroup(TaskGroup),
+    Config(Config),
 }
 
 #[derive(Debug, Clone, serde::Serialize, TypeScriptify)]
@@ -30,27 +31,102 @@ impl ItemWrap {
     }
 }
 
+///
+/// The 'flat' format used in hand-written documents, where `kind` sits
+/// alongside the item's own fields
+///
+/// ```yaml
+/// kind: DependencyCheck
+/// name: install node
+/// verify: node -v
+/// ```
+///
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "kind")]
+pub enum KindTagged {
+    Command(Command),
+    FileExistsCheck(FileExistsCheck),
+    DependencyCheck(DependencyCheck),
+    Instruction(Instruction),
+    HostEntriesCheck(HostEntriesCheck),
+    Topic(Topic),
+    TaskGroup(TaskGroup),
+    #[serde(alias = "ConfigMap")]
+    Config(Config),
+}
+
+impl From<KindTagged> for Item {
+    fn from(tagged: KindTagged) -> Self {
+        match tagged {
+            KindTagged::Command(cmd) => Item::Command(cmd),
+            KindTagged::FileExistsCheck(fec) => Item::FileExistsCheck(fec),
+            KindTagged::DependencyCheck(dc) => Item::DependencyCheck(dc),
+            KindTagged::Instruction(inst) => Item::Instruction(inst),
+            KindTagged::HostEntriesCheck(hec) => Item::HostEntriesCheck(hec),
+            KindTagged::Topic(topic) => Item::Topic(topic),
+            KindTagged::TaskGroup(tg) => Item::TaskGroup(tg),
+            KindTagged::Config(config) => Item::Config(config),
+        }
+    }
+}
+
+///
+/// Entries in a list of steps/deps can be:
+///
+/// - a plain string, which is a named reference to another item
+/// - an inline item in the 'flat' format, see [`KindTagged`]
+/// - the `kind` + `content` format that [`ItemWrap`] serializes to
+///
+impl<'de> serde::Deserialize<'de> for ItemWrap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        #[serde(tag = "kind", content = "content")]
+        enum Wrapped {
+            NamedRef(LineMarker<String>),
+            Item(Item),
+        }
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Named(String),
+            Wrapped(Wrapped),
+            Flat(KindTagged),
+        }
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Named(name) => ItemWrap::NamedRef(LineMarker::new(name, None)),
+            Repr::Wrapped(Wrapped::NamedRef(line_marker)) => ItemWrap::NamedRef(line_marker),
+            Repr::Wrapped(Wrapped::Item(item)) => ItemWrap::Item(item),
+            Repr::Flat(tagged) => ItemWrap::Item(Item::from(tagged)),
+        })
+    }
+}
+
 impl Item {
     pub fn set_name(&mut self, name: &str) {
         match self {
             Item::Command(cmd) => cmd.name = name.into(),
-            Item::FileExistsCheck(fec) => fec.name = name.to_string(),
+            Item::FileExistsCheck(fec) => fec.name = name.into(),
             Item::DependencyCheck(dc) => dc.name = name.into(),
             Item::Instruction(inst) => inst.name = name.into(),
-            Item::HostEntriesCheck(hec) => hec.name = name.to_string(),
+            Item::HostEntriesCheck(hec) => hec.name = name.into(),
             Item::Topic(top) => top.name = name.into(),
-            Item::TaskGroup(tg) => tg.name = name.to_string(),
+            Item::TaskGroup(tg) => tg.name = name.into(),
+            Item::Config(config) => config.name = name.into(),
         };
     }
     pub fn name(&self) -> String {
         match self {
             Item::Command(cmd) => cmd.name.to_string(),
-            Item::FileExistsCheck(fec) => fec.name.clone(),
+            Item::FileExistsCheck(fec) => fec.name.to_string(),
             Item::DependencyCheck(dc) => dc.name.to_string(),
             Item::Instruction(inst) => inst.name.to_string(),
-            Item::HostEntriesCheck(hec) => hec.name.clone(),
+            Item::HostEntriesCheck(hec) => hec.name.to_string(),
             Item::Topic(top) => top.name.to_string(),
-            Item::TaskGroup(tg) => tg.name.clone(),
+            Item::TaskGroup(tg) => tg.name.to_string(),
+            Item::Config(config) => config.name.to_string(),
         }
     }
     pub fn kind_name(&self) -> String {
@@ -62,6 +138,7 @@ impl Item {
             Item::HostEntriesCheck(_) => "Host Entries Check",
             Item::Topic(_) => "Topic",
             Item::TaskGroup(_) => "Task Group",
+            Item::Config(_) => "Config",
         }
         .to_string()
     }
@@ -71,34 +148,56 @@ impl Item {
             Item::Command(cmd) => cmd.name.set_line_start(line_start),
             Item::Topic(topic) => topic.name.set_line_start(line_start),
             Item::DependencyCheck(dep_check) => dep_check.name.set_line_start(line_start),
-            _i => todo!("set line start {}", _i.name()),
+            Item::FileExistsCheck(fec) => fec.name.set_line_start(line_start),
+            Item::HostEntriesCheck(hec) => hec.name.set_line_start(line_start),
+            Item::TaskGroup(tg) => tg.name.set_line_start(line_start),
+            Item::Config(config) => config.name.set_line_start(line_start),
+        }
+    }
+    ///
+    /// Remove the line of the name + every dep/step, for items whose lines
+    /// came from a different file than the one being read
+    ///
+    pub fn clear_line_starts(&mut self) {
+        let name = match self {
+            Item::Instruction(inst) => &mut inst.name,
+            Item::Command(cmd) => &mut cmd.name,
+            Item::Topic(topic) => &mut topic.name,
+            Item::DependencyCheck(dep_check) => &mut dep_check.name,
+            Item::FileExistsCheck(fec) => &mut fec.name,
+            Item::HostEntriesCheck(hec) => &mut hec.name,
+            Item::TaskGroup(tg) => &mut tg.name,
+            Item::Config(config) => &mut config.name,
+        };
+        name.line_start = None;
+        let lists = match self {
+            Item::Topic(topic) => vec![&mut topic.deps, &mut topic.steps],
+            Item::TaskGroup(tg) => vec![&mut tg.steps],
+            _ => vec![],
+        };
+        for item_wrap in lists.into_iter().flatten() {
+            match item_wrap {
+                ItemWrap::NamedRef(marker) => marker.line_start = None,
+                ItemWrap::Item(inline) => inline.clear_line_starts(),
+            }
         }
     }
 }
 
 pub fn name_ref(item: &Item) -> &String {
-    match item {
-        Item::Command(cmd) => &cmd.name.item,
-        // Item::FileExistsCheck(_) => {}
-        Item::DependencyCheck(dpc) => &dpc.name.item,
-        Item::Instruction(inst) => &inst.name.item,
-        // Item::HostEntriesCheck(_) => {}
-        Item::Topic(t) => &t.name.item,
-        // Item::TaskGroup(_) => {}
-        _ => todo!("linemarker"),
-    }
+    &marker_ref(item).item
 }
 
 pub fn marker_ref(item: &Item) -> &LineMarker<String> {
     match item {
         Item::Command(cmd) => &cmd.name,
-        // Item::FileExistsCheck(_) => {}
+        Item::FileExistsCheck(fec) => &fec.name,
         Item::DependencyCheck(dpc) => &dpc.name,
         Item::Instruction(inst) => &inst.name,
-        // Item::HostEntriesCheck(_) => {}
+        Item::HostEntriesCheck(hec) => &hec.name,
         Item::Topic(t) => &t.name,
-        // Item::TaskGroup(_) => {}
-        _ => todo!("linemarker"),
+        Item::TaskGroup(tg) => &tg.name,
+        Item::Config(config) => &config.name,
     }
 }
 
@@ -107,11 +206,19 @@ impl FromStr for Item {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "FileExistsCheck" | "fec" => Ok(Item::FileExistsCheck(Default::default())),
+            "FileExistsCheck" | "File Exists Check" | "fec" => {
+                Ok(Item::FileExistsCheck(Default::default()))
+            }
+            "HostEntriesCheck" | "Host Entries Check" | "hosts" => {
+                Ok(Item::HostEntriesCheck(Default::default()))
+            }
             "Topic" | "topic" => Ok(Item::Topic(Default::default())),
-            "TaskGroup" | "tg" | "task-group" => Ok(Item::TaskGroup(Default::default())),
+            "TaskGroup" | "Task Group" | "tg" | "task-group" => {
+                Ok(Item::TaskGroup(Default::default()))
+            }
             "Command" | "command" | "cmd" => Ok(Item::Command(Default::default())),
             "Instruction" | "inst" | "instruction" => Ok(Item::Instruction(Default::default())),
+            "Config" | "config" => Ok(Item::Config(Default::default())),
             "DependencyCheck" | "Dependency Check" | "dep" | "dep-check" => {
                 Ok(Item::DependencyCheck(Default::default()))
             }
@@ -54,10 +54,36 @@ impl PartialEq<String> for LineMarker<String> {
 
 impl<T> Display for LineMarker<T>
 where
-    T: Debug + Default + Eq + Hash + PartialEq + Clone,
+    T: Debug + Display + Default + Eq + Hash + PartialEq + Clone,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", *self)
+        write!(f, "{}", self.item)
+    }
+}
+
+///
+/// Hand-written documents give names as plain strings, but [`LineMarker`]
+/// serializes as `{ line_start, item }` - both are accepted so
+/// that serialized output can be read back in again.
+///
+impl<'de> serde::Deserialize<'de> for LineMarker<String> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Plain(String),
+            Marked {
+                line_start: Option<u32>,
+                item: String,
+            },
+        }
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Plain(item) => LineMarker::new(item, None),
+            Repr::Marked { line_start, item } => LineMarker::new(item, line_start),
+        })
     }
 }
 
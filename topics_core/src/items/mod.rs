@@ -1,4 +1,5 @@
 pub use command::*;
+pub use config::*;
 pub use dependency::*;
 pub use file_exists::*;
 pub use host::*;
@@ -9,6 +10,7 @@ pub use task_group::*;
 pub use topic::*;
 
 pub mod command;
+pub mod config;
 pub mod dependency;
 pub mod file_exists;
 pub mod host;
@@ -1,16 +1,18 @@
 use crate::items::item::ItemWrap;
+use crate::items::LineMarker;
 use typescript_definitions::TypeScriptify;
 
-#[derive(Debug, Clone, serde::Serialize, TypeScriptify)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, TypeScriptify)]
 pub struct TaskGroup {
-    pub name: String,
+    pub name: LineMarker<String>,
+    #[serde(default)]
     pub steps: Vec<ItemWrap>,
 }
 
 impl Default for TaskGroup {
     fn default() -> Self {
         Self {
-            name: "Machine setup".to_string(),
+            name: LineMarker::new("Machine setup", None),
             steps: vec![],
         }
     }
@@ -1,10 +1,12 @@
 use crate::items::{ItemWrap, LineMarker};
 use typescript_definitions::TypeScriptify;
 
-#[derive(Debug, Clone, serde::Serialize, TypeScriptify)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, TypeScriptify)]
 pub struct Topic {
     pub name: LineMarker<String>,
+    #[serde(default)]
     pub steps: Vec<ItemWrap>,
+    #[serde(default)]
     pub deps: Vec<ItemWrap>,
 }
 
@@ -9,17 +9,28 @@ mod cli;
 mod context;
 pub mod cwd;
 mod db;
+mod discover;
 mod doc;
 pub mod doc_src;
+mod explain;
 pub mod items;
+mod lsp;
+mod plan;
 mod print;
+mod run;
+mod verify;
+mod watch;
 
 pub mod db_error;
+pub mod diagnostic;
 pub mod doc_err;
+pub mod graph;
 pub mod html;
 mod html_template;
+pub mod markdown;
 pub mod opt;
 pub mod output;
+pub mod shell;
 
 pub use db_error::*;
 pub use doc_err::*;
@@ -48,6 +59,11 @@ pub fn from_opt(opts: &Opt) -> SubCommandResult<()> {
             SubCommandItems::Print(print) => print.exec(&ctx),
             SubCommandItems::Generate(gen) => gen.exec(&ctx),
             SubCommandItems::Verify(verify) => verify.exec(&ctx),
+            SubCommandItems::Run(run) => run.exec(&ctx),
+            SubCommandItems::Graph(graph) => graph.exec(&ctx),
+            SubCommandItems::Plan(plan) => plan.exec(&ctx),
+            SubCommandItems::Lsp(lsp) => lsp.exec(&ctx),
+            SubCommandItems::Explain(explain) => explain.exec(&ctx),
         },
         None => {
             println!("no command given");
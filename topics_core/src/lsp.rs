@@ -0,0 +1,580 @@
+use crate::context::Context;
+use crate::db::{with_db, Db};
+use crate::db_error::SerializedError;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::discover::EXTENSIONS;
+use crate::doc::{Doc, DocResult};
+use crate::items::{marker_ref, Item, ItemWrap};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+///
+/// Every file the server knows about, along with the content of any that are
+/// open in the editor - those may have unsaved changes, so always win over what's on disk.
+///
+/// Paths are canonical, so that the same file can't be loaded twice under different names
+///
+#[derive(Debug, Default)]
+pub struct Workspace {
+    files: Vec<PathBuf>,
+    open: HashMap<PathBuf, String>,
+}
+
+impl Workspace {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        let mut unique = vec![];
+        for pb in files.iter().map(|pb| canonical(pb)) {
+            if !unique.contains(&pb) {
+                unique.push(pb);
+            }
+        }
+        Self {
+            files: unique,
+            open: HashMap::new(),
+        }
+    }
+
+    pub fn open(&mut self, pb: PathBuf, content: String) {
+        let pb = canonical(&pb);
+        let supported = pb
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map_or(false, |ext| EXTENSIONS.contains(&ext));
+        if supported && !self.files.contains(&pb) {
+            self.files.push(pb.clone());
+        }
+        self.open.insert(pb, content);
+    }
+
+    pub fn close(&mut self, pb: &Path) {
+        self.open.remove(&canonical(pb));
+    }
+
+    fn read_docs(&self, ctx: &Context) -> Vec<DocResult<Doc>> {
+        self.files
+            .iter()
+            .map(|pb| match self.open.get(pb) {
+                Some(content) => Doc::from_content(pb, content, ctx),
+                None => Doc::from_path_buf(pb, ctx),
+            })
+            .collect()
+    }
+
+    ///
+    /// Call `f` with a graph built from every document that parsed without errors
+    ///
+    fn with_db<T>(&self, ctx: &Context, f: impl for<'a> FnOnce(&Db<'a>) -> T) -> T {
+        let docs = self
+            .read_docs(ctx)
+            .into_iter()
+            .filter_map(|doc| doc.ok())
+            .filter(|doc| doc.errors.is_empty())
+            .collect::<Vec<Doc>>();
+        with_db(&docs, |db, _errors| f(db))
+    }
+
+    ///
+    /// Diagnostics for every file, including an empty list for files without
+    /// problems so that previous diagnostics are cleared
+    ///
+    pub fn diagnostics(&self, ctx: &Context) -> BTreeMap<PathBuf, Vec<Diagnostic>> {
+        let mut output: BTreeMap<PathBuf, Vec<Diagnostic>> =
+            self.files.iter().map(|pb| (pb.clone(), vec![])).collect();
+        let mut good = vec![];
+        for (pb, doc) in self.files.iter().zip(self.read_docs(ctx)) {
+            match doc {
+                Ok(doc) if doc.errors.is_empty() => good.push(doc),
+                doc => {
+                    for diagnostic in Diagnostic::from_doc_result(&doc) {
+                        // the editor only cares which open file the problem belongs to
+                        output.entry(pb.clone()).or_default().push(Diagnostic {
+                            file: Some(pb.clone()),
+                            ..diagnostic
+                        });
+                    }
+                }
+            }
+        }
+        let db_errors = with_db(&good, |_db, errors| {
+            errors
+                .into_iter()
+                .map(SerializedError::from)
+                .collect::<Vec<SerializedError>>()
+        });
+        // every site of an error is marked, so a cycle shows up on each of its lines
+        for diagnostic in db_errors.iter().map(Diagnostic::from_db_error) {
+            let sites = diagnostic
+                .related
+                .iter()
+                .map(|site| (&site.file, site.line));
+            for (file, line) in std::iter::once((&diagnostic.file, diagnostic.line)).chain(sites) {
+                if let Some(pb) = file {
+                    output.entry(pb.clone()).or_default().push(Diagnostic {
+                        file: Some(pb.clone()),
+                        line,
+                        ..diagnostic.clone()
+                    });
+                }
+            }
+        }
+        output
+    }
+
+    ///
+    /// Where the item referred to on `line` is defined, as `(file, line)`
+    ///
+    pub fn definition(&self, ctx: &Context, pb: &Path, line: u32) -> Option<(PathBuf, u32)> {
+        let pb = canonical(pb);
+        self.with_db(ctx, |db| {
+            let item = named_ref_at(db, &pb, line)?;
+            let (src, _) = db.get(&item.name())?;
+            let input_file = src.input_file()?.clone();
+            let line = src.line_in_file(marker_ref(item).line_start).unwrap_or(1);
+            Some((input_file, line))
+        })
+    }
+
+    ///
+    /// A summary of the item referred to, or defined, on `line`
+    ///
+    pub fn hover(&self, ctx: &Context, pb: &Path, line: u32) -> Option<String> {
+        let pb = canonical(pb);
+        self.with_db(ctx, |db| {
+            named_ref_at(db, &pb, line)
+                .or_else(|| defined_at(db, &pb, line))
+                .map(hover_card)
+        })
+    }
+
+    ///
+    /// Every item name as `(name, kind)`, sorted by name
+    ///
+    pub fn completions(&self, ctx: &Context) -> Vec<(String, String)> {
+        self.with_db(ctx, |db| {
+            let mut names = db
+                .item_lookup
+                .iter()
+                .map(|(name, (_, item))| ((*name).clone(), item.kind_name()))
+                .collect::<Vec<(String, String)>>();
+            names.sort();
+            names
+        })
+    }
+}
+
+///
+/// Files that don't exist yet (eg: new + unsaved) are kept as they are
+///
+fn canonical(pb: &Path) -> PathBuf {
+    pb.canonicalize().unwrap_or_else(|_| pb.to_path_buf())
+}
+
+///
+/// The item that a Topic or Task Group in `pb` refers to by name on `line`
+///
+fn named_ref_at<'a>(db: &Db<'a>, pb: &Path, line: u32) -> Option<&'a Item> {
+    for item in db.all_items() {
+        let entries: Vec<&ItemWrap> = match item {
+            Item::Topic(topic) => topic.deps.iter().chain(topic.steps.iter()).collect(),
+            Item::TaskGroup(tg) => tg.steps.iter().collect(),
+            _ => vec![],
+        };
+        let src = match db.get(&item.name()) {
+            Some((src, _)) if src.input_file().map(PathBuf::as_path) == Some(pb) => *src,
+            _ => continue,
+        };
+        for entry in entries {
+            if let ItemWrap::NamedRef(marker) = entry {
+                if src.line_in_file(marker.line_start) == Some(line) {
+                    return db.get(&marker.item).map(|(_, item)| *item);
+                }
+            }
+        }
+    }
+    None
+}
+
+///
+/// The item whose heading (or `name`) is on `line` of `pb`
+///
+fn defined_at<'a>(db: &Db<'a>, pb: &Path, line: u32) -> Option<&'a Item> {
+    db.item_lookup.values().find_map(|(src, item)| {
+        let same_file = src.input_file().map(PathBuf::as_path) == Some(pb);
+        if same_file && src.line_in_file(marker_ref(item).line_start) == Some(line) {
+            Some(*item)
+        } else {
+            None
+        }
+    })
+}
+
+fn hover_card(item: &Item) -> String {
+    let mut card = format!("**{}**: {}", item.kind_name(), item.name());
+    let details = match item {
+        Item::Command(cmd) => format!("```shell\n{}\n```", cmd.command.trim()),
+        Item::DependencyCheck(dc) => format!("```shell\n{}\n```", dc.verify.trim()),
+        Item::Instruction(inst) => inst.instruction.clone().unwrap_or_default(),
+        Item::FileExistsCheck(fec) => format!("`{}`", fec.path.display()),
+        Item::HostEntriesCheck(hec) => hec
+            .hosts
+            .iter()
+            .map(|host| format!("- {}", host.domain))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        Item::Topic(topic) => {
+            let mut sections = vec![];
+            if !topic.deps.is_empty() {
+                sections.push(format!("Dependencies:\n\n{}", names(&topic.deps)));
+            }
+            if !topic.steps.is_empty() {
+                sections.push(format!("Steps:\n\n{}", names(&topic.steps)));
+            }
+            sections.join("\n\n")
+        }
+        Item::TaskGroup(tg) => names(&tg.steps),
+        Item::Config(_) => String::new(),
+    };
+    if !details.is_empty() {
+        card.push_str("\n\n");
+        card.push_str(&details);
+    }
+    card
+}
+
+fn names(entries: &[ItemWrap]) -> String {
+    entries
+        .iter()
+        .map(|entry| match entry {
+            ItemWrap::NamedRef(marker) => format!("- {}", marker.item),
+            ItemWrap::Item(inline) => format!("- {}: {}", inline.kind_name(), inline.name()),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+///
+/// Read a single JSON-RPC message, `Ok(None)` once the input has closed.
+///
+/// `Some(Err(reason))` when the message couldn't be parsed, which is answered with
+/// a parse error rather than stopping the server
+///
+pub fn read_message(input: &mut impl BufRead) -> std::io::Result<Option<Result<Value, String>>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let length = match length {
+        Some(length) => length,
+        None => return Ok(Some(Err(String::from("missing `Content-Length` header")))),
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(
+        serde_json::from_slice(&body).map_err(|e| format!("invalid JSON: {}", e)),
+    ))
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> std::io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+///
+/// Serve requests until the editor sends `exit`, or closes stdin
+///
+pub fn serve(
+    workspace: &mut Workspace,
+    ctx: &Context,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut published: HashSet<PathBuf> = HashSet::new();
+    while let Some(message) = read_message(input)? {
+        let message = match message {
+            Ok(message) => message,
+            Err(reason) => {
+                write_message(
+                    output,
+                    &json!({
+                        "jsonrpc": "2.0",
+                        "id": Value::Null,
+                        "error": { "code": -32700, "message": reason }
+                    }),
+                )?;
+                continue;
+            }
+        };
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let position = |params: &Value| {
+            let pb = params["textDocument"]["uri"].as_str().and_then(uri_to_path);
+            let line = params["position"]["line"]
+                .as_u64()
+                .map(|line| line as u32 + 1);
+            pb.zip(line)
+        };
+        let result = match method {
+            "initialize" => Some(json!({
+                "capabilities": {
+                    // the full text is sent on every change
+                    "textDocumentSync": 1,
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                    "completionProvider": {}
+                },
+                "serverInfo": { "name": "topics" }
+            })),
+            "shutdown" => Some(Value::Null),
+            "exit" => return Ok(()),
+            "textDocument/definition" => Some(
+                position(params)
+                    .and_then(|(pb, line)| workspace.definition(ctx, &pb, line))
+                    .map_or(
+                        Value::Null,
+                        |(pb, line)| json!({ "uri": path_to_uri(&pb), "range": line_range(line) }),
+                    ),
+            ),
+            "textDocument/hover" => Some(
+                position(params)
+                    .and_then(|(pb, line)| workspace.hover(ctx, &pb, line))
+                    .map_or(
+                        Value::Null,
+                        |card| json!({ "contents": { "kind": "markdown", "value": card } }),
+                    ),
+            ),
+            "textDocument/completion" => Some(Value::Array(
+                workspace
+                    .completions(ctx)
+                    .into_iter()
+                    .map(|(name, kind)| json!({ "label": name, "detail": kind }))
+                    .collect(),
+            )),
+            _ => None,
+        };
+
+        // every notification that changes a document means diagnostics need publishing again
+        let changed = match method {
+            "initialized" => true,
+            "textDocument/didOpen" => {
+                let doc = &params["textDocument"];
+                if let (Some(pb), Some(text)) = (
+                    doc["uri"].as_str().and_then(uri_to_path),
+                    doc["text"].as_str(),
+                ) {
+                    workspace.open(pb, text.to_string());
+                }
+                true
+            }
+            "textDocument/didChange" => {
+                let pb = params["textDocument"]["uri"].as_str().and_then(uri_to_path);
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                if let (Some(pb), Some(text)) = (pb, text) {
+                    workspace.open(pb, text.to_string());
+                }
+                true
+            }
+            "textDocument/didClose" => {
+                if let Some(pb) = params["textDocument"]["uri"].as_str().and_then(uri_to_path) {
+                    workspace.close(&pb);
+                }
+                true
+            }
+            "textDocument/didSave" => true,
+            _ => false,
+        };
+
+        match (result, message.get("id")) {
+            (Some(result), Some(id)) => write_message(
+                output,
+                &json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            )?,
+            (None, Some(id)) => write_message(
+                output,
+                &json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": format!("`{}` is not supported", method) }
+                }),
+            )?,
+            _ => {}
+        }
+
+        if changed {
+            for (pb, diagnostics) in workspace.diagnostics(ctx) {
+                // files that never had problems don't need an empty list
+                if diagnostics.is_empty() && !published.remove(&pb) {
+                    continue;
+                }
+                if !diagnostics.is_empty() {
+                    published.insert(pb.clone());
+                }
+                let diagnostics = diagnostics
+                    .into_iter()
+                    .map(|diagnostic| {
+                        json!({
+                            "range": line_range(diagnostic.line.unwrap_or(1)),
+                            "severity": match diagnostic.severity {
+                                Severity::Error => 1,
+                                Severity::Warning => 2,
+                            },
+                            "source": "topics",
+                            "code": diagnostic.code,
+                            "message": diagnostic.message
+                        })
+                    })
+                    .collect::<Vec<Value>>();
+                write_message(
+                    output,
+                    &json!({
+                        "jsonrpc": "2.0",
+                        "method": "textDocument/publishDiagnostics",
+                        "params": { "uri": path_to_uri(&pb), "diagnostics": diagnostics }
+                    }),
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+///
+/// The whole of a (1-based) line, as a 0-based LSP range
+///
+fn line_range(line: u32) -> Value {
+    let line = line.saturating_sub(1);
+    json!({
+        "start": { "line": line, "character": 0 },
+        "end": { "line": line + 1, "character": 0 }
+    })
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+fn path_to_uri(pb: &Path) -> String {
+    let encoded = pb
+        .to_string_lossy()
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect::<String>();
+    format!("file://{}", encoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn workspace(files: &[&str]) -> (Workspace, Context) {
+        let ctx = Context::default();
+        let files = files.iter().map(|file| ctx.join_path(file)).collect();
+        (Workspace::new(files), ctx)
+    }
+
+    #[test]
+    fn test_definition_and_hover() {
+        let (ws, ctx) = workspace(&["../fixtures/md/inline.md"]);
+        let pb = canonical(&ctx.join_path("../fixtures/md/inline.md"));
+        // `- run the build` in the Steps of `Build the app`
+        assert_eq!(ws.definition(&ctx, &pb, 22), Some((pb.clone(), 26)));
+        assert_eq!(
+            ws.hover(&ctx, &pb, 22),
+            Some(String::from(
+                "**Command**: run the build\n\n```shell\nyarn build\n```"
+            ))
+        );
+        assert_eq!(ws.definition(&ctx, &pb, 2), None);
+    }
+
+    #[test]
+    fn test_unsaved_content_wins() {
+        let (mut ws, ctx) = workspace(&["../fixtures/md/inline.md"]);
+        // the editor sends the canonical path, which is the same file as the one loaded
+        let pb = canonical(&ctx.join_path("../fixtures/md/inline.md"));
+        ws.open(
+            pb.clone(),
+            String::from("# Topic: Build the app\n\n## Steps\n\n- run teh build\n"),
+        );
+        let diagnostics = ws.diagnostics(&ctx);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[&pb].len(), 1);
+        assert_eq!(diagnostics[&pb][0].line, Some(5));
+        assert_eq!(diagnostics[&pb][0].code.as_deref(), Some("E002"));
+        let names = ws
+            .completions(&ctx)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<String>>();
+        assert_eq!(names, vec!["Build the app"]);
+    }
+
+    #[test]
+    fn test_messages_and_uris() {
+        let mut input =
+            std::io::Cursor::new(b"Content-Length: 17\r\n\r\n{\"method\":\"exit\"}".to_vec());
+        let message = read_message(&mut input).expect("read").expect("message");
+        assert_eq!(message.expect("valid")["method"], "exit");
+        assert!(read_message(&mut input).expect("read").is_none());
+
+        // a bad message is answered with a parse error, and the server keeps going
+        let mut input = std::io::Cursor::new(
+            b"Content-Length: 5\r\n\r\n{oopsContent-Length: 17\r\n\r\n{\"method\":\"exit\"}"
+                .to_vec(),
+        );
+        let mut output = vec![];
+        serve(
+            &mut Workspace::default(),
+            &Context::default(),
+            &mut input,
+            &mut output,
+        )
+        .expect("serve");
+        let output = String::from_utf8(output).expect("utf8");
+        assert!(output.contains(r#""code":-32700"#));
+
+        let pb = PathBuf::from("/docs/my topics.md");
+        assert_eq!(path_to_uri(&pb), "file:///docs/my%20topics.md");
+        assert_eq!(uri_to_path(&path_to_uri(&pb)), Some(pb));
+    }
+}
@@ -0,0 +1,314 @@
+use crate::db::{config_lookup, split_warnings};
+use crate::db_error::SerializedError;
+use crate::doc_src::ItemSrc;
+use crate::items::{Instruction, Item, ItemWrap};
+use crate::DbError;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+///
+/// A Markdown 'handbook' - one page per Topic, with every
+/// dependency + step resolved and expanded inline
+///
+#[derive(Debug, Default)]
+pub struct MdOutput {
+    pub pages: Vec<MdPage>,
+    pub errors: Vec<SerializedError>,
+    pub warnings: Vec<SerializedError>,
+}
+
+#[derive(Debug)]
+pub struct MdPage {
+    pub pb: PathBuf,
+    pub title: String,
+    pub content: String,
+}
+
+impl MdOutput {
+    ///
+    /// All pages joined into a single document, used when printing to stdout
+    ///
+    pub fn as_single_doc(&self) -> String {
+        self.pages
+            .iter()
+            .map(|page| page.content.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+    ///
+    /// An index page linking to every topic page, used when writing to a directory
+    ///
+    pub fn index_page(&self) -> MdPage {
+        let mut content = String::new();
+        let _ = writeln!(content, "# Topics\n");
+        for page in &self.pages {
+            let _ = writeln!(content, "- [{}]({})", page.title, page.pb.display());
+        }
+        MdPage {
+            pb: PathBuf::from("index.md"),
+            title: String::from("Topics"),
+            content,
+        }
+    }
+}
+
+pub fn output_md<'a>(
+    lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
+    items: &'a Vec<(ItemSrc<'_>, Vec<Item>)>,
+    errors: Vec<DbError<'a>>,
+) -> MdOutput {
+    let (errors, warnings) = split_warnings(errors);
+    let mut output = MdOutput {
+        errors,
+        warnings,
+        ..Default::default()
+    };
+    // `index.md` is reserved for the index page
+    let mut slugs = HashMap::new();
+    slugs.insert(String::from("index"), 1);
+    for (src, items) in items {
+        for item in items {
+            if let Item::Topic(topic) = item {
+                let mut content = String::new();
+                let mut seen = vec![&topic.name.item];
+                let _ = writeln!(content, "# {}\n", topic.name.item);
+                write_section(
+                    &mut content,
+                    "Dependencies",
+                    &topic.deps,
+                    *src,
+                    lookup,
+                    &mut seen,
+                );
+                write_section(&mut content, "Steps", &topic.steps, *src, lookup, &mut seen);
+                output.pages.push(MdPage {
+                    pb: PathBuf::from(format!("{}.md", unique_slug(&topic.name.item, &mut slugs))),
+                    title: topic.name.item.clone(),
+                    content,
+                });
+            }
+        }
+    }
+    output
+}
+
+fn write_section<'a>(
+    output: &mut String,
+    title: &str,
+    entries: &'a [ItemWrap],
+    src: ItemSrc<'a>,
+    lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
+    seen: &mut Vec<&'a String>,
+) {
+    if entries.is_empty() {
+        return;
+    }
+    let _ = writeln!(output, "## {}\n", title);
+    write_entries(output, entries, 3, src, lookup, seen);
+}
+
+fn write_entries<'a>(
+    output: &mut String,
+    entries: &'a [ItemWrap],
+    level: usize,
+    src: ItemSrc<'a>,
+    lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
+    seen: &mut Vec<&'a String>,
+) {
+    for entry in entries {
+        match entry {
+            ItemWrap::NamedRef(line_marker) => match lookup.get(&line_marker.item) {
+                Some((item_src, item)) => write_item(output, item, level, *item_src, lookup, seen),
+                None => {
+                    let _ = writeln!(output, "{} {}\n", heading(level), line_marker.item);
+                    let _ = writeln!(output, "_missing: no item with this name was found_\n");
+                }
+            },
+            ItemWrap::Item(item) => write_item(output, item, level, src, lookup, seen),
+        }
+    }
+}
+
+fn write_item<'a>(
+    output: &mut String,
+    item: &'a Item,
+    level: usize,
+    src: ItemSrc<'a>,
+    lookup: &'a HashMap<&'a String, (ItemSrc<'a>, &'a Item)>,
+    seen: &mut Vec<&'a String>,
+) {
+    let _ = writeln!(output, "{} {}\n", heading(level), item.name());
+    match item {
+        Item::Command(cmd) => {
+            let _ = writeln!(output, "- directory: `{}`\n", cmd.cwd);
+            write_fence(output, &cmd.interpolated(config_lookup(lookup)));
+        }
+        Item::FileExistsCheck(fec) => {
+            let _ = writeln!(output, "- directory: `{}`", fec.cwd.display());
+            let _ = writeln!(output, "- file: `{}`\n", fec.path.display());
+        }
+        Item::DependencyCheck(dc) => {
+            if let Some(url) = &dc.url {
+                let _ = writeln!(output, "[{url}]({url})\n", url = url);
+            }
+            write_fence(output, &dc.verify);
+            if let Some(autofix) = &dc.autofix {
+                let _ = writeln!(output, "To fix:\n");
+                write_fence(output, autofix);
+            }
+        }
+        Item::Instruction(inst) => {
+            let body = instruction_body(src, inst);
+            if !body.trim().is_empty() {
+                let _ = writeln!(output, "{}\n", body.trim_end());
+            }
+        }
+        Item::HostEntriesCheck(hec) => {
+            for entry in &hec.hosts {
+                let _ = writeln!(output, "- `127.0.0.1  {}`", entry.domain);
+            }
+            let _ = writeln!(output);
+        }
+        Item::Topic(topic) => {
+            if !seen.contains(&&topic.name.item) {
+                seen.push(&topic.name.item);
+                write_entries(output, &topic.deps, level + 1, src, lookup, seen);
+                write_entries(output, &topic.steps, level + 1, src, lookup, seen);
+                seen.pop();
+            }
+        }
+        Item::TaskGroup(tg) => {
+            if !seen.contains(&&tg.name.item) {
+                seen.push(&tg.name.item);
+                write_entries(output, &tg.steps, level + 1, src, lookup, seen);
+                seen.pop();
+            }
+        }
+        Item::Config(config) => {
+            let mut values = config.values.iter().collect::<Vec<(&String, &String)>>();
+            values.sort();
+            for (key, value) in values {
+                let _ = writeln!(output, "- `{}`: `{}`", key, value);
+            }
+            let _ = writeln!(output);
+        }
+    }
+}
+
+///
+/// Markdown instructions are rendered from the original AST, minus their heading,
+/// everything else (eg: from YAML, or inline within a Topic) has the text already
+///
+pub(crate) fn instruction_body(src: ItemSrc, inst: &Instruction) -> String {
+    match (
+        src.md(),
+        &inst.instruction,
+        inst.ast_range.without_heading(),
+    ) {
+        (_, Some(instruction), _) => instruction.clone(),
+        (Some(md_src), None, Some(range)) => md_src.range_as_markdown(range),
+        _ => String::new(),
+    }
+}
+
+fn write_fence(output: &mut String, content: &str) {
+    let _ = writeln!(output, "```shell");
+    let _ = writeln!(output, "{}", content.trim_end());
+    let _ = writeln!(output, "```\n");
+}
+
+/// Markdown only supports 6 levels of heading
+fn heading(level: usize) -> String {
+    "#".repeat(level.min(6))
+}
+
+///
+/// File names for topic pages, eg: `Run all unit tests` -> `run-all-unit-tests`
+///
+pub(crate) fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+///
+/// Like [`slug`], but names that collide with an earlier one get a numeric suffix,
+/// eg: `Run tests` + `Run: tests` -> `run-tests` + `run-tests-2`
+///
+pub(crate) fn unique_slug(name: &str, used: &mut HashMap<String, usize>) -> String {
+    let base = slug(name);
+    let mut count = used.get(&base).copied().unwrap_or(0);
+    loop {
+        count += 1;
+        let candidate = match count {
+            1 => base.clone(),
+            n => format!("{}-{}", base, n),
+        };
+        if !used.contains_key(&candidate) {
+            used.insert(base, count);
+            used.insert(candidate.clone(), 1);
+            return candidate;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::Context;
+    use crate::db::try_from_docs;
+    use crate::print::OutputKind;
+    use crate::Outputs;
+
+    #[test]
+    fn test_md_handbook() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/markdown/handbook.md")]);
+        let output = match try_from_docs(&f, &OutputKind::Markdown) {
+            Ok(Outputs::Markdown(output)) => output,
+            _ => unreachable!(),
+        };
+        assert!(output.errors.is_empty());
+        assert_eq!(output.pages.len(), 1);
+        let page = &output.pages[0];
+        assert_eq!(page.pb, PathBuf::from("run-the-app.md"));
+        let expected = "# Run the app
+
+## Dependencies
+
+### Read the README
+
+Please read the **README** first.
+
+## Steps
+
+### start the server
+
+- directory: `./app`
+
+```shell
+yarn start
+```
+
+";
+        assert_eq!(page.content, expected);
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Run all unit tests"), "run-all-unit-tests");
+        assert_eq!(slug("Build: the app!"), "build-the-app");
+    }
+
+    #[test]
+    fn test_unique_slug() {
+        let mut used = HashMap::new();
+        assert_eq!(unique_slug("Run tests", &mut used), "run-tests");
+        assert_eq!(unique_slug("Run: tests", &mut used), "run-tests-2");
+        assert_eq!(unique_slug("Run tests 2", &mut used), "run-tests-2-2");
+        assert_eq!(unique_slug("run tests!", &mut used), "run-tests-3");
+    }
+}
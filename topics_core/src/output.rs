@@ -2,16 +2,19 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::db_error::SerializedError;
-use crate::doc_src::{MdDocSource, MdSrc};
+use crate::doc_src::{ItemSrc, MdDocSource};
 use crate::html::HtmlOutput;
-use crate::items::{Item, LineMarker};
-use crate::{DbError, ErrorRef};
+use crate::items::Item;
+use crate::markdown::MdOutput;
+use crate::DbError;
 use typescript_definitions::TypeScriptify;
 
+#[derive(Debug)]
 pub enum Outputs {
     Plain(Output),
     Json(Output),
     Html(HtmlOutput),
+    Markdown(MdOutput),
 }
 
 #[derive(Debug, Default, TypeScriptify, serde::Serialize)]
@@ -21,24 +24,17 @@ pub struct Output {
     pub errors: Vec<SerializedError>,
 }
 
-pub fn output<'a>(
-    graph: &'a HashMap<&'a String, Vec<&'a LineMarker<String>>>,
-    lookup: &'a HashMap<&'a String, (&'a MdSrc<'a>, &'a Item)>,
-    items: &'a Vec<(&'_ MdSrc, Vec<Item>)>,
-) -> Output {
+pub fn output<'a>(items: &'a Vec<(ItemSrc<'_>, Vec<Item>)>, errors: Vec<DbError<'a>>) -> Output {
     let mut output = Output::default();
-    let cycles = detect_cycle(graph, lookup);
-    for c in cycles {
-        match c {
-            DbError::Cycle(ErrorRef { inner, .. }) => {
-                output.errors.push(SerializedError::Cycle(inner));
-            }
-        }
+    for err in errors {
+        output.errors.push(SerializedError::from(err));
     }
-    for (md_src, items) in items {
-        if let Some(pb) = &md_src.md_doc_src.input_file {
-            let pb = pb.clone();
-            output.docs.entry(pb).or_insert(md_src.md_doc_src.clone());
+    for (src, items) in items {
+        if let ItemSrc::Md(md_src) = src {
+            if let Some(pb) = &md_src.md_doc_src.input_file {
+                let pb = pb.clone();
+                output.docs.entry(pb).or_insert(md_src.md_doc_src.clone());
+            }
         }
         for item in items {
             output.items.push(item.clone()); // todo: How to remove this clone...
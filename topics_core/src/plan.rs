@@ -0,0 +1,102 @@
+use crate::db::Db;
+use crate::items::Item;
+use crate::run::{plan_order, RunError};
+use std::fmt::Write;
+
+///
+/// The flattened list of items that `run` would execute for one or more Topics
+///
+#[derive(Debug, serde::Serialize)]
+pub struct Plan<'a> {
+    pub topics: Vec<String>,
+    pub steps: Vec<&'a Item>,
+}
+
+pub fn plan<'a>(db: &Db<'a>, topic_names: &[String]) -> Result<Plan<'a>, RunError> {
+    let names = topic_names
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<&str>>();
+    Ok(Plan {
+        topics: topic_names.to_vec(),
+        steps: plan_order(db, &names)?,
+    })
+}
+
+impl<'a> Plan<'a> {
+    ///
+    /// One numbered line per step, eg: `1. Command: install deps`
+    ///
+    pub fn to_plain(&self) -> String {
+        let mut output = String::new();
+        for (index, item) in self.steps.iter().enumerate() {
+            let _ = writeln!(
+                output,
+                "{}. {}: {}",
+                index + 1,
+                item.kind_name(),
+                item.name()
+            );
+        }
+        output
+    }
+
+    ///
+    /// A checklist that can be pasted into an issue or PR
+    ///
+    pub fn to_markdown(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "# Plan: {}", self.topics.join(", "));
+        let _ = writeln!(output);
+        for item in &self.steps {
+            let _ = writeln!(output, "- [ ] {}: **{}**", item.kind_name(), item.name());
+        }
+        output
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::Context;
+    use crate::db::with_db;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_plan_shared_steps_listed_once() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/plan/topics.yaml")]);
+        let topics = vec![String::from("Run the app"), String::from("Run the tests")];
+        let markdown = with_db(&f, |db, _errors| {
+            plan(db, &topics).expect("plan").to_markdown()
+        });
+        let expected = r#"# Plan: Run the app, Run the tests
+
+- [ ] Dependency Check: **node installed**
+- [ ] Command: **install dependencies**
+- [ ] Command: **start the server**
+- [ ] Command: **yarn test**
+"#;
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn test_plan_refuses_cycles() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/cycle/topics.md")]);
+        let message = with_db(&f, |db, _errors| {
+            match plan(db, &[String::from("Build the app")]) {
+                Err(err @ RunError::Cycle { .. }) => err.to_string(),
+                _ => unreachable!(),
+            }
+        });
+        assert_eq!(
+            message,
+            "`Build the app`, `Install dependencies`, `Run the app` form a cycle, so there is no order to run them in"
+        );
+    }
+}
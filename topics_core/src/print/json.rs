@@ -0,0 +1,67 @@
+use crate::context::Context;
+use crate::db_error::SerializedError;
+use crate::diagnostic::{to_json, Diagnostic};
+use crate::doc::{Doc, DocResult};
+use crate::print::Print;
+
+///
+/// Prints problems as a JSON array of [`Diagnostic`]s on stdout, for other tools to consume.
+/// Anything meant for people goes to stderr, so that stdout is always valid JSON
+///
+#[derive(Debug)]
+pub struct JsonPrinter;
+
+impl Print for JsonPrinter {
+    fn print_welcome(&self, _docs: &[Doc], _ctx: &Context) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn print_error(&self, msg: &str, _ctx: &Context) -> anyhow::Result<()> {
+        println!("{}", to_json(&[Diagnostic::from_message(msg)])?);
+        Ok(())
+    }
+
+    fn print_heading(&self, kind: &str, message: &str) {
+        eprintln!("{} {}", kind, message);
+    }
+
+    fn print_errors(&self, docs: &[DocResult<Doc>], _ctx: &Context) -> anyhow::Result<()> {
+        let diagnostics = docs
+            .iter()
+            .flat_map(Diagnostic::from_doc_result)
+            .collect::<Vec<Diagnostic>>();
+        println!("{}", to_json(&diagnostics)?);
+        Ok(())
+    }
+
+    fn print_db_errors(&self, errors: &[SerializedError], _ctx: &Context) -> anyhow::Result<()> {
+        let diagnostics = errors
+            .iter()
+            .map(Diagnostic::from_db_error)
+            .collect::<Vec<Diagnostic>>();
+        println!("{}", to_json(&diagnostics)?);
+        Ok(())
+    }
+
+    ///
+    /// Warnings go into the same document as errors would, which is empty when there are none
+    ///
+    fn print_valid(&self, warnings: &[SerializedError], ctx: &Context) -> anyhow::Result<()> {
+        self.print_db_errors(warnings, ctx)
+    }
+
+    fn print_all_errors(
+        &self,
+        docs: &[DocResult<Doc>],
+        errors: &[SerializedError],
+        _ctx: &Context,
+    ) -> anyhow::Result<()> {
+        let diagnostics = docs
+            .iter()
+            .flat_map(Diagnostic::from_doc_result)
+            .chain(errors.iter().map(Diagnostic::from_db_error))
+            .collect::<Vec<Diagnostic>>();
+        println!("{}", to_json(&diagnostics)?);
+        Ok(())
+    }
+}
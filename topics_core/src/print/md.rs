@@ -1,20 +1,44 @@
 use crate::context::Context;
-use crate::doc::Doc;
+use crate::db_error::SerializedError;
+use crate::doc::{Doc, DocResult};
 
 // use crate::output::OutputDoc;
-use crate::print::Print;
+use crate::print::{PlainPrinter, Print};
 
+///
+/// Prints the handbook on stdout, so everything else is printed the same as
+/// [`PlainPrinter`] would, on stderr
+///
 #[derive(Debug)]
 pub struct MdPrinter;
 
 impl Print for MdPrinter {
-    fn print_welcome(&self, _docs: &[Doc], _ctx: &Context) -> anyhow::Result<()> {
-        println!("later..");
+    fn print_welcome(&self, docs: &[Doc], _ctx: &Context) -> anyhow::Result<()> {
+        PlainPrinter.print_heading(
+            "Topics",
+            &format!("Writing a handbook for {} document(s)", docs.len()),
+        );
         Ok(())
     }
 
-    fn print_error(&self, _msg: &str, _ctx: &Context) -> anyhow::Result<()> {
-        unimplemented!()
+    fn print_error(&self, msg: &str, ctx: &Context) -> anyhow::Result<()> {
+        PlainPrinter.print_error(msg, ctx)
+    }
+
+    fn print_heading(&self, kind: &str, message: &str) {
+        PlainPrinter.print_heading(kind, message);
+    }
+
+    fn print_errors(&self, docs: &[DocResult<Doc>], ctx: &Context) -> anyhow::Result<()> {
+        PlainPrinter.print_errors(docs, ctx)
+    }
+
+    fn print_db_errors(&self, errors: &[SerializedError], _ctx: &Context) -> anyhow::Result<()> {
+        // stdout is reserved for the generated markdown
+        for err in errors {
+            eprintln!("{}", err);
+        }
+        Ok(())
     }
 }
 
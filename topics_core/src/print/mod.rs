@@ -1,5 +1,10 @@
+pub mod json;
 pub mod md;
 pub mod plain;
+pub use json::*;
 pub use plain::*;
 pub mod print;
 pub use print::*;
+pub mod sarif;
+pub use sarif::*;
+pub mod snippet;
@@ -1,4 +1,5 @@
 use crate::db::Db;
+use crate::db_error::SerializedError;
 use crate::doc_err::{DocError, Location};
 use crate::items::Item;
 use crate::{
@@ -6,11 +7,9 @@ use crate::{
     doc::Doc,
     doc::DocResult,
     items::{ItemWrap, Topic},
+    print::snippet::{render, Label},
     print::Print,
 };
-use bat::line_range::{LineRange, LineRanges};
-use bat::Input;
-use bat::PrettyPrinter;
 use std::io::ErrorKind;
 use std::path::PathBuf;
 
@@ -45,12 +44,11 @@ impl Print for PlainPrinter {
         Ok(())
     }
 
-    fn print_all(&self, docs: &[Doc], _db: &Db, _ctx: &Context) -> anyhow::Result<()> {
-        for _doc in docs {
-            todo!("print_all:: print doc")
-            // for topic in &doc.topics() {
-            //     let _ = self.print_topic(&topic, &db, &doc, &ctx);
-            // }
+    fn print_all(&self, docs: &[Doc], db: &Db, ctx: &Context) -> anyhow::Result<()> {
+        for topic in topics(db) {
+            if let Some(doc) = doc_for(topic, docs, db) {
+                self.print_topic(topic, db, doc, ctx)?;
+            }
         }
         Ok(())
     }
@@ -68,16 +66,18 @@ impl Print for PlainPrinter {
                             acc.push((1, pb.clone()));
                             acc
                         }
-                        DocError::SerdeLocationErr(loc_err) => {
+                        DocError::SerdeLocationErr(loc_err)
+                        | DocError::MissingHeading(loc_err)
+                        | DocError::InvalidCodeFence(loc_err)
+                        | DocError::MissingContent(loc_err) => {
                             if let Some(pb) = &loc_err.input_file {
                                 acc.push((1, pb.clone()));
                             }
                             acc
                         }
-                        DocError::Unknown(_e) => {
-                            todo!("how to handle this...")
-                        }
-                        DocError::NotSupported(pb) => {
+                        // not tied to a file, so only shown in full below
+                        DocError::Unknown(_e) => acc,
+                        DocError::NotSupported(pb) | DocError::MissingExtension(pb) => {
                             acc.push((1, pb.clone()));
                             acc
                         }
@@ -113,6 +113,88 @@ impl Print for PlainPrinter {
         }
         Ok(())
     }
+
+    fn print_db_errors(&self, errors: &[SerializedError], ctx: &Context) -> anyhow::Result<()> {
+        // warnings get their own section, so they aren't counted as errors
+        let (warnings, errors): (Vec<&SerializedError>, Vec<&SerializedError>) =
+            errors.iter().partition(|err| err.is_warning());
+        print_warning_list(&warnings, ctx);
+        if errors.is_empty() {
+            return Ok(());
+        }
+        print_error_heading(
+            "Problems detected",
+            &format!(
+                "{} error{} found in the graph, please review the following:",
+                errors.len(),
+                if errors.len() == 1 { "" } else { "s" }
+            ),
+        );
+        print_db_error_list(&errors, ctx);
+        if let Some(err) = errors.first() {
+            eprintln!();
+            eprintln!(
+                "    For more information about an error, try `topics explain {}`",
+                err.code()
+            );
+        }
+        Ok(())
+    }
+
+    fn print_warnings(&self, warnings: &[SerializedError], ctx: &Context) -> anyhow::Result<()> {
+        print_warning_list(&warnings.iter().collect::<Vec<&SerializedError>>(), ctx);
+        Ok(())
+    }
+}
+
+fn print_warning_list(warnings: &[&SerializedError], ctx: &Context) {
+    if warnings.is_empty() {
+        return;
+    }
+    use ansi_term::Colour::Yellow;
+    eprintln!();
+    eprintln!(
+        "{} {}",
+        Yellow.paint("[Warnings]"),
+        Yellow.bold().paint(format!(
+            "{} warning{} found in the graph",
+            warnings.len(),
+            if warnings.len() == 1 { "" } else { "s" }
+        ))
+    );
+    print_db_error_list(warnings, ctx);
+}
+
+fn print_db_error_list(errors: &[&SerializedError], ctx: &Context) {
+    let read = |pb: Option<&PathBuf>| std::fs::read_to_string(ctx.join_path(pb?)).ok();
+    for err in errors {
+        eprintln!();
+        let labels = err
+            .labels()
+            .into_iter()
+            .filter_map(|(file, line, message)| {
+                Some(Label {
+                    file: file.cloned(),
+                    line: line?,
+                    column: None,
+                    message,
+                    context: None,
+                })
+            })
+            .collect::<Vec<Label>>();
+        let text = err.to_string();
+        match render(&labels, read, true) {
+            // the snippet replaces the `check line N` details of the message
+            Some(snippet) => {
+                eprintln!("    {}", text.lines().next().unwrap_or_default());
+                eprintln!("    {}", snippet.trim_end().replace('\n', "\n    "));
+                if let Some(help) = err.help() {
+                    eprintln!("    = help: {}", help);
+                }
+            }
+            None => eprintln!("    {}", text.replace('\n', "\n    ")),
+        }
+    }
 }
 
 fn print_item_line(item: &Item, db: &Db, width: usize) {
@@ -143,31 +225,56 @@ fn print_item_line(item: &Item, db: &Db, width: usize) {
                 }
             }
         }
-        Item::TaskGroup(_) => {}
+        Item::TaskGroup(tg) => {
+            if !tg.steps.is_empty() {
+                println!("{:1$}- Steps:", " ", width + 2);
+                for item_wrap in &tg.steps {
+                    print_item_wrap(item_wrap, db, width + 4);
+                }
+            }
+        }
+        Item::Config(_) => {}
+    }
+}
+
+fn print_item_wrap(item_wrap: &ItemWrap, db: &Db, width: usize) {
+    match item_wrap {
+        ItemWrap::NamedRef(line_marker) => match db.get(&line_marker.item) {
+            Some((_, matched_item)) => print_item_line(matched_item, db, width),
+            None => println!(
+                "{:width$}- NOT_FOUND: {name}",
+                " ",
+                width = width,
+                name = line_marker.item
+            ),
+        },
+        ItemWrap::Item(item) => print_item_line(item, db, width),
     }
 }
 
-fn print_item_wrap(_item_wrap: &ItemWrap, _db: &Db, _width: usize) {
-    // let maybe_item = match item_wrap {
-    //     ItemWrap::NamedRef(name) => (name.item.clone(), db.item_map.get(&name.item)),
-    //     ItemWrap::Item(item) => {
-    //         let name = item.name();
-    //         (name.clone(), db.item_map.get(&name))
-    //     }
-    // };
-    // match maybe_item {
-    //     (_name, Some(matched_item)) => {
-    //         print_item_line(&matched_item.item, &db, width);
-    //     }
-    //     (name, None) => {
-    //         println!(
-    //             "{:width$}- NOT_FOUND: {name}",
-    //             " ",
-    //             width = width,
-    //             name = name
-    //         );
-    //     }
-    // }
+///
+/// Every Topic in the graph, in the order they were defined
+///
+pub fn topics<'a>(db: &Db<'a>) -> Vec<&'a Topic> {
+    db.all_items()
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::Topic(topic) => Some(topic),
+            _ => None,
+        })
+        .collect()
+}
+
+///
+/// The document that a Topic was defined in
+///
+pub fn doc_for<'d>(topic: &Topic, docs: &'d [Doc], db: &Db) -> Option<&'d Doc> {
+    let input_file = db
+        .get(&topic.name.item)
+        .and_then(|(src, _)| src.input_file());
+    docs.iter()
+        .find(|doc| doc.source.file().as_ref() == input_file)
+        .or_else(|| docs.first())
 }
 
 fn print_error_heading(kind: &str, message: &str) {
@@ -215,101 +322,41 @@ fn print_error(doc_err: &DocError) {
             eprintln!();
             eprintln!("    input: {}", Green.paint(&pb.display().to_string()));
         }
-        DocError::Unknown(_) | DocError::NotSupported(_) => {
+        DocError::Unknown(_) | DocError::NotSupported(_) | DocError::MissingExtension(_) => {
             print_error_heading("Error", &doc_err.to_string());
         }
-        DocError::SerdeLocationErr(loc_err) => {
-            let name = loc_err
-                .input_file
-                .as_ref()
-                .map(|f| f.display().to_string())
-                .unwrap_or_default();
+        DocError::SerdeLocationErr(loc_err)
+        | DocError::MissingHeading(loc_err)
+        | DocError::InvalidCodeFence(loc_err)
+        | DocError::MissingContent(loc_err) => {
             print_error_heading("error", &loc_err.description);
-            if let Some(error_loc) = &loc_err.location {
-                match error_loc {
-                    Location::LineAndColRegion {
-                        line,
-                        line_end,
-                        line_start,
-                        ..
-                    } => {
-                        let mut printer = setup();
-
-                        printer.highlight(*line);
-
-                        // regions
-                        ranges(&mut printer, *line_start, *line_end);
-                        inputs(
-                            &mut printer,
-                            &name,
-                            loc_err.input_file_src.as_bytes(),
-                            *line,
-                            &loc_err.input_file,
-                        );
-
-                        printer.print().unwrap();
-                    }
-                    Location::Region {
-                        line_end,
-                        line_start,
-                    } => {
-                        let mut printer = setup();
-                        ranges(&mut printer, *line_start, *line_end);
-                        inputs(
-                            &mut printer,
-                            &name,
-                            loc_err.input_file_src.as_bytes(),
-                            *line_start,
-                            &loc_err.input_file,
-                        );
-                        printer.print().unwrap();
-                    }
-                    Location::LineAndCol { line, column: _ } => {
-                        let mut printer = setup();
-                        printer.highlight(*line);
-                        inputs(
-                            &mut printer,
-                            &name,
-                            loc_err.input_file_src.as_bytes(),
-                            *line,
-                            &loc_err.input_file,
-                        );
-                        printer.print().unwrap();
-                    }
-                }
+            // a region is shown in full, eg: the whole YAML document that failed to parse
+            let (line, column, context) = match &loc_err.location {
+                Some(Location::LineAndCol { line, column }) => (*line, Some(*column), None),
+                Some(Location::LineAndColRegion {
+                    line,
+                    column,
+                    line_start,
+                    line_end,
+                }) => (*line, Some(*column), Some((*line_start, *line_end))),
+                Some(Location::Region {
+                    line_start,
+                    line_end,
+                }) => (*line_start, None, Some((*line_start, *line_end))),
+                None => return,
+            };
+            let label = Label {
+                file: loc_err.input_file.clone(),
+                line: line as u32,
+                column: column.map(|column| column as u32),
+                message: String::new(),
+                context: context.map(|(start, end)| (start as u32, end as u32)),
+            };
+            let source = |_: Option<&PathBuf>| Some(loc_err.input_file_src.clone());
+            if let Some(snippet) = render(&[label], source, true) {
+                eprintln!();
+                eprintln!("    {}", snippet.trim_end().replace('\n', "\n    "));
             }
         }
     }
 }
-
-fn setup() -> PrettyPrinter<'static> {
-    let mut printer = PrettyPrinter::new();
-    printer.header(true).line_numbers(true).grid(true);
-    printer
-}
-
-fn ranges(printer: &mut PrettyPrinter, start: usize, end: usize) {
-    printer.line_ranges(LineRanges::from(vec![LineRange::new(start, end)]));
-}
-
-fn inputs<'a>(
-    printer: &mut PrettyPrinter<'a>,
-    name: &'a str,
-    bytes: &'a [u8],
-    line: usize,
-    pb: &'a Option<PathBuf>,
-) {
-    let filename = pb
-        .as_ref()
-        .map(|f| f.display().to_string())
-        .unwrap_or_default();
-    let file_name_heading = if line > 0 {
-        format!("{}:{}", filename, line)
-    } else {
-        filename
-    };
-    printer.inputs(vec![Input::from_bytes(bytes)
-        .name(&name) // Dummy name provided to detect the syntax.
-        .kind("File")
-        .title(file_name_heading)]);
-}
@@ -1,8 +1,9 @@
 use crate::context::Context;
 use crate::db::Db;
+use crate::db_error::SerializedError;
 use crate::doc::{Doc, DocResult};
 use crate::items::topic::Topic;
-use crate::print::{md, plain};
+use crate::print::{json, md, plain, sarif};
 use std::fmt::{Debug, Display, Formatter};
 use std::str::FromStr;
 
@@ -44,6 +45,50 @@ pub trait Print: Debug {
         }
         Ok(())
     }
+    ///
+    /// Problems found in the graph. Like warnings, these go to stderr by default
+    ///
+    fn print_db_errors(&self, errors: &[SerializedError], _ctx: &Context) -> anyhow::Result<()> {
+        for err in errors {
+            eprintln!("{}", err);
+        }
+        Ok(())
+    }
+    ///
+    /// Problems that don't stop a command, see [`SerializedError::is_warning`].
+    /// These always go to stderr, so that they can't interfere with any other output
+    ///
+    fn print_warnings(&self, warnings: &[SerializedError], _ctx: &Context) -> anyhow::Result<()> {
+        for warning in warnings {
+            eprintln!("{}", warning);
+        }
+        Ok(())
+    }
+    ///
+    /// The end of a command that found no errors. Machine readable printers still
+    /// print a document here, so that there's always something on stdout to consume
+    ///
+    fn print_valid(&self, warnings: &[SerializedError], ctx: &Context) -> anyhow::Result<()> {
+        self.print_warnings(warnings, ctx)
+    }
+    ///
+    /// Documents that couldn't be read, along with any problems in the graph built
+    /// from the rest. Machine readable printers combine these into a single document
+    ///
+    fn print_all_errors(
+        &self,
+        docs: &[DocResult<Doc>],
+        errors: &[SerializedError],
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        if !docs.is_empty() {
+            self.print_errors(docs, ctx)?;
+        }
+        if !errors.is_empty() {
+            self.print_db_errors(errors, ctx)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -52,6 +97,7 @@ pub enum OutputKind {
     Markdown,
     Json,
     Html,
+    Sarif,
 }
 
 impl Display for OutputKind {
@@ -72,23 +118,28 @@ impl Print for OutputKind {
             OutputKind::Markdown => (md::MdPrinter).print_welcome(docs, ctx),
             OutputKind::Plain => (plain::PlainPrinter).print_welcome(docs, ctx),
             OutputKind::Html => (plain::PlainPrinter).print_welcome(docs, ctx),
-            OutputKind::Json => {
-                todo!("implement json")
-            }
+            OutputKind::Json => (json::JsonPrinter).print_welcome(docs, ctx),
+            OutputKind::Sarif => (sarif::SarifPrinter).print_welcome(docs, ctx),
         }
     }
 
     fn print_error(&self, msg: &str, ctx: &Context) -> anyhow::Result<()> {
         match self {
             OutputKind::Plain => (plain::PlainPrinter).print_error(msg, ctx),
-            _ => todo!("implement others for print_topic"),
+            OutputKind::Json => (json::JsonPrinter).print_error(msg, ctx),
+            OutputKind::Sarif => (sarif::SarifPrinter).print_error(msg, ctx),
+            OutputKind::Markdown => (md::MdPrinter).print_error(msg, ctx),
+            OutputKind::Html => (plain::PlainPrinter).print_error(msg, ctx),
         }
     }
 
     fn print_heading(&self, kind: &str, message: &str) {
         match self {
             OutputKind::Plain => (plain::PlainPrinter).print_heading(kind, message),
-            _ => todo!("implement others for print_topic"),
+            OutputKind::Json => (json::JsonPrinter).print_heading(kind, message),
+            OutputKind::Sarif => (sarif::SarifPrinter).print_heading(kind, message),
+            OutputKind::Markdown => (md::MdPrinter).print_heading(kind, message),
+            OutputKind::Html => (plain::PlainPrinter).print_heading(kind, message),
         }
     }
 
@@ -103,7 +154,7 @@ impl Print for OutputKind {
         match self {
             OutputKind::Markdown => (md::MdPrinter).print_all(docs, &db, ctx),
             OutputKind::Plain => (plain::PlainPrinter).print_all(docs, &db, ctx),
-            OutputKind::Json => {
+            OutputKind::Json | OutputKind::Sarif => {
                 todo!("implement json")
             }
             OutputKind::Html => {
@@ -115,12 +166,54 @@ impl Print for OutputKind {
         match self {
             OutputKind::Markdown => (md::MdPrinter).print_errors(docs, ctx),
             OutputKind::Plain => (plain::PlainPrinter).print_errors(docs, ctx),
-            OutputKind::Json => {
-                todo!("implement json")
+            OutputKind::Json => (json::JsonPrinter).print_errors(docs, ctx),
+            OutputKind::Sarif => (sarif::SarifPrinter).print_errors(docs, ctx),
+            OutputKind::Html => (plain::PlainPrinter).print_errors(docs, ctx),
+        }
+    }
+    fn print_db_errors(&self, errors: &[SerializedError], ctx: &Context) -> anyhow::Result<()> {
+        match self {
+            OutputKind::Markdown => (md::MdPrinter).print_db_errors(errors, ctx),
+            OutputKind::Json => (json::JsonPrinter).print_db_errors(errors, ctx),
+            OutputKind::Sarif => (sarif::SarifPrinter).print_db_errors(errors, ctx),
+            OutputKind::Plain | OutputKind::Html => {
+                (plain::PlainPrinter).print_db_errors(errors, ctx)
             }
-            OutputKind::Html => {
-                todo!("implement json")
+        }
+    }
+    fn print_warnings(&self, warnings: &[SerializedError], ctx: &Context) -> anyhow::Result<()> {
+        match self {
+            OutputKind::Plain | OutputKind::Html => {
+                (plain::PlainPrinter).print_warnings(warnings, ctx)
+            }
+            OutputKind::Markdown => (md::MdPrinter).print_warnings(warnings, ctx),
+            OutputKind::Json => (json::JsonPrinter).print_warnings(warnings, ctx),
+            OutputKind::Sarif => (sarif::SarifPrinter).print_warnings(warnings, ctx),
+        }
+    }
+    fn print_valid(&self, warnings: &[SerializedError], ctx: &Context) -> anyhow::Result<()> {
+        match self {
+            OutputKind::Plain | OutputKind::Html => {
+                (plain::PlainPrinter).print_valid(warnings, ctx)
+            }
+            OutputKind::Markdown => (md::MdPrinter).print_valid(warnings, ctx),
+            OutputKind::Json => (json::JsonPrinter).print_valid(warnings, ctx),
+            OutputKind::Sarif => (sarif::SarifPrinter).print_valid(warnings, ctx),
+        }
+    }
+    fn print_all_errors(
+        &self,
+        docs: &[DocResult<Doc>],
+        errors: &[SerializedError],
+        ctx: &Context,
+    ) -> anyhow::Result<()> {
+        match self {
+            OutputKind::Plain | OutputKind::Html => {
+                (plain::PlainPrinter).print_all_errors(docs, errors, ctx)
             }
+            OutputKind::Markdown => (md::MdPrinter).print_all_errors(docs, errors, ctx),
+            OutputKind::Json => (json::JsonPrinter).print_all_errors(docs, errors, ctx),
+            OutputKind::Sarif => (sarif::SarifPrinter).print_all_errors(docs, errors, ctx),
         }
     }
 }
@@ -134,6 +227,7 @@ impl FromStr for OutputKind {
             "json" | "Json" => Ok(OutputKind::Json),
             "plain" | "Plain" => Ok(OutputKind::Plain),
             "html" | "Html" => Ok(OutputKind::Html),
+            "sarif" | "Sarif" => Ok(OutputKind::Sarif),
             _a => Err(PrintKindError::Unknown),
         }
     }
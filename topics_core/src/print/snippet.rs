@@ -0,0 +1,274 @@
+use ansi_term::Colour::{Blue, Cyan, Red};
+use ansi_term::Style;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+///
+/// A line that an error points at, along with what's wrong with it
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub file: Option<PathBuf>,
+    /// starts at 1
+    pub line: u32,
+    /// starts at 1, when missing the whole line (minus any list marker) is underlined
+    pub column: Option<u32>,
+    pub message: String,
+    /// the first + last line of a region around `line` to show as well,
+    /// eg: the YAML document that an error is in
+    pub context: Option<(u32, u32)>,
+}
+
+impl Label {
+    ///
+    /// The lines to show for this label, clamped to the `len` lines of its file
+    ///
+    fn region(&self, len: usize) -> (u32, u32) {
+        let (start, end) = self.context.unwrap_or((self.line, self.line));
+        (
+            start.max(1).min(self.line),
+            end.min(len as u32).max(self.line),
+        )
+    }
+}
+
+///
+/// Lines further apart than this are separated by `...` rather than printed in full
+///
+const MAX_GAP: u32 = 3;
+
+///
+/// Render an excerpt of every file that `labels` point at, in the style of `rustc`:
+///
+/// ```text
+///    --> docs/setup.md:21
+///    |
+/// 21 | - Install dependencies
+///    |   ^^^^^^^^^^^^^^^^^^^^ `Run the app` refers to `Install dependencies`
+/// ```
+///
+/// The first label is the primary one (`^`), the rest are secondary (`-`) and are grouped
+/// by file. `source` gives the content of each file, labels in files it can't give are skipped.
+///
+/// `None` when there was nothing to show
+///
+pub fn render(
+    labels: &[Label],
+    source: impl Fn(Option<&PathBuf>) -> Option<String>,
+    colour: bool,
+) -> Option<String> {
+    let paint = |style: Style, text: &str| {
+        if colour {
+            style.paint(text).to_string()
+        } else {
+            text.to_string()
+        }
+    };
+
+    // keep files in the order they're first seen, so that the primary label's file is first
+    let mut files: Vec<Option<&PathBuf>> = vec![];
+    for label in labels {
+        if !files.contains(&label.file.as_ref()) {
+            files.push(label.file.as_ref());
+        }
+    }
+
+    let mut groups = vec![];
+    for file in files {
+        let content = match source(file) {
+            Some(content) => content,
+            None => continue,
+        };
+        let lines = content.lines().map(String::from).collect::<Vec<String>>();
+        let mut in_file = labels
+            .iter()
+            .enumerate()
+            .filter(|(_, label)| label.file.as_ref() == file)
+            .filter(|(_, label)| label.line > 0 && label.line as usize <= lines.len())
+            .collect::<Vec<(usize, &Label)>>();
+        if in_file.is_empty() {
+            continue;
+        }
+        // the header points at the primary label, or the first line in this file
+        let header = in_file[0].1.line;
+        in_file.sort_by_key(|(index, label)| (label.line, *index));
+        groups.push((file, header, lines, in_file));
+    }
+
+    let last_line = groups
+        .iter()
+        .flat_map(|(_, _, lines, in_file)| {
+            in_file
+                .iter()
+                .map(move |(_, label)| label.region(lines.len()).1)
+        })
+        .max()?;
+    let width = last_line.to_string().len();
+    let gutter = |num: &str| paint(Blue.bold(), &format!("{:>width$} |", num, width = width));
+
+    let mut output = String::new();
+    for (index, (file, header, lines, in_file)) in groups.iter().enumerate() {
+        let arrow = if index == 0 { "-->" } else { ":::" };
+        let name = file.map(|pb| pb.display().to_string()).unwrap_or_default();
+        let _ = writeln!(
+            output,
+            "{:width$}{} {}:{}",
+            "",
+            paint(Blue.bold(), arrow),
+            name,
+            header,
+            width = width + 1
+        );
+        let _ = writeln!(output, "{}", gutter(""));
+
+        // the last line that was printed
+        let mut previous: Option<u32> = None;
+        for (position, (label_index, label)) in in_file.iter().enumerate() {
+            let (context_start, context_end) = label.region(lines.len());
+            if previous.map_or(true, |prev| prev < label.line) {
+                // a few lines between labels are shown in full, more than that are elided
+                let first = match previous {
+                    Some(prev) if label.line - prev <= MAX_GAP => prev + 1,
+                    Some(prev) => {
+                        let _ = writeln!(output, "{}", paint(Blue.bold(), "..."));
+                        context_start.max(prev + 1)
+                    }
+                    None => context_start,
+                };
+                for line in first..=label.line {
+                    let text = &lines[line as usize - 1];
+                    let _ = writeln!(output, "{} {}", gutter(&line.to_string()), text);
+                }
+                previous = Some(label.line);
+            }
+
+            let text = &lines[label.line as usize - 1];
+            let (start, len) = underline(text, label.column);
+            let (mark, style) = if *label_index == 0 {
+                ("^", Red.bold())
+            } else {
+                ("-", Cyan.bold())
+            };
+            let marks = format!("{} {}", mark.repeat(len), label.message);
+            let _ = writeln!(
+                output,
+                "{} {}{}",
+                gutter(""),
+                " ".repeat(start),
+                paint(style, marks.trim_end())
+            );
+
+            // the rest of the context, up to the line of the next label
+            let until = match in_file.get(position + 1) {
+                Some((_, next)) => context_end.min(next.line.saturating_sub(1)),
+                None => context_end,
+            };
+            for line in label.line + 1..=until {
+                let text = &lines[line as usize - 1];
+                let _ = writeln!(output, "{} {}", gutter(&line.to_string()), text);
+                previous = Some(line);
+            }
+        }
+        let _ = writeln!(output, "{}", gutter(""));
+    }
+    Some(output)
+}
+
+///
+/// Where the underline starts, and how long it is - skipping indentation
+/// and list markers so that it sits under the name of the item itself
+///
+fn underline(text: &str, column: Option<u32>) -> (usize, usize) {
+    let len = text.trim_end().chars().count();
+    let start = match column {
+        Some(column) => (column as usize).saturating_sub(1).min(len),
+        None => {
+            let indent = text.chars().take_while(|c| c.is_whitespace()).count();
+            let rest = text.trim_start();
+            let marker = ["- ", "* ", "+ "]
+                .iter()
+                .find(|marker| rest.starts_with(*marker))
+                .map_or(0, |marker| marker.len());
+            indent + marker
+        }
+    };
+    (start, len.saturating_sub(start).max(1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_primary_and_secondary() {
+        let md = "# Topic: Install dependencies\n\n## Steps\n\n- Build the app\n\n---\n\n# Topic: Build the app\n\n## Steps\n\n- Install dependencies\n";
+        let file = Some(PathBuf::from("topics.md"));
+        let labels = vec![
+            Label {
+                file: file.clone(),
+                line: 13,
+                column: None,
+                message: String::from("`Build the app` refers to `Install dependencies`"),
+                context: None,
+            },
+            Label {
+                file,
+                line: 5,
+                column: None,
+                message: String::from("`Install dependencies` refers to `Build the app`"),
+                context: None,
+            },
+        ];
+        let output = render(&labels, |_| Some(md.to_string()), false).expect("snippet");
+        let expected = r#"   --> topics.md:13
+   |
+ 5 | - Build the app
+   |   ------------- `Install dependencies` refers to `Build the app`
+...
+13 | - Install dependencies
+   |   ^^^^^^^^^^^^^^^^^^^^ `Build the app` refers to `Install dependencies`
+   |
+"#;
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_render_skips_unknown_files() {
+        let label = Label {
+            file: Some(PathBuf::from("missing.md")),
+            line: 1,
+            column: Some(3),
+            message: String::new(),
+            context: None,
+        };
+        assert_eq!(render(&[label.clone()], |_| None, false), None);
+        let output = render(&[label], |_| Some(String::from("kind: Command")), false);
+        assert_eq!(
+            output.as_deref(),
+            Some("  --> missing.md:1\n  |\n1 | kind: Command\n  |   ^^^^^^^^^^^\n  |\n")
+        );
+    }
+
+    #[test]
+    fn test_render_context() {
+        let yaml = "kind: Command\nname: Build\ncwd: [\ncommand: yarn build\n---\nkind: Topic\n";
+        let label = Label {
+            file: Some(PathBuf::from("topics.yaml")),
+            line: 3,
+            column: Some(6),
+            message: String::new(),
+            context: Some((1, 4)),
+        };
+        let output = render(&[label], |_| Some(yaml.to_string()), false).expect("snippet");
+        let expected = r#"  --> topics.yaml:3
+  |
+1 | kind: Command
+2 | name: Build
+3 | cwd: [
+  |      ^
+4 | command: yarn build
+  |
+"#;
+        assert_eq!(output, expected);
+    }
+}
@@ -0,0 +1,304 @@
+use crate::context::Context;
+use crate::db::{config_lookup, cycle_edges, Db};
+use crate::items::{Item, ItemWrap, HOSTS_FILE};
+use crate::markdown::instruction_body;
+use std::collections::{BTreeSet, HashSet};
+use std::process::{Command, ExitStatus};
+
+#[derive(thiserror::Error, Debug)]
+pub enum RunError {
+    #[error("Topic `{name}` was not found{}", did_you_mean(.suggestions))]
+    TopicNotFound {
+        name: String,
+        suggestions: Vec<String>,
+    },
+    #[error("`{name}` is a {kind}, only Topics can be run")]
+    NotATopic { name: String, kind: String },
+    #[error("`{}` form a cycle, so there is no order to run them in", .names.join("`, `"))]
+    Cycle { names: Vec<String> },
+    #[error("`{reference}` is referenced from `{parent}`, but no item with that name was found")]
+    MissingRef { parent: String, reference: String },
+    #[error("{kind} `{name}` could not be started: {original}")]
+    Spawn {
+        kind: String,
+        name: String,
+        original: std::io::Error,
+    },
+    #[error("{kind} `{name}` failed ({reason}), {completed} of {total} step(s) completed")]
+    Failed {
+        kind: String,
+        name: String,
+        reason: String,
+        completed: usize,
+        total: usize,
+    },
+}
+
+pub(crate) fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(", did you mean `{}`?", suggestions.join("`, `"))
+    }
+}
+
+///
+/// Every runnable item a Topic needs, in dependency order.
+///
+/// Dependencies are resolved before steps, nested Topics + Task Groups are
+/// expanded in place, and an item that's needed more than once only runs the first time.
+/// Dependency Checks are moved to the front, so that nothing runs before every
+/// check has passed.
+///
+pub fn run_order<'a>(db: &Db<'a>, topic_name: &str) -> Result<Vec<&'a Item>, RunError> {
+    plan_order(db, &[topic_name])
+}
+
+///
+/// The same as [`run_order`], but for several Topics at once.
+///
+/// An item reached through more than one Topic is only listed the first time, and
+/// nothing is planned when any of the items involved are part of a cycle
+///
+pub fn plan_order<'a>(db: &Db<'a>, topic_names: &[&str]) -> Result<Vec<&'a Item>, RunError> {
+    let mut order = vec![];
+    let mut seen = HashSet::new();
+    for topic_name in topic_names {
+        let topic = match db.get(topic_name) {
+            Some((_, item @ Item::Topic(_))) => *item,
+            Some((_, item)) => {
+                return Err(RunError::NotATopic {
+                    name: topic_name.to_string(),
+                    kind: item.kind_name(),
+                })
+            }
+            None => {
+                return Err(RunError::TopicNotFound {
+                    name: topic_name.to_string(),
+                    suggestions: db.suggestions(topic_name),
+                })
+            }
+        };
+        visit_item(db, topic, &mut order, &mut seen)?;
+    }
+    let names = cycle_edges(db.graph)
+        .into_iter()
+        .filter(|(from, _)| seen.contains(*from))
+        .flat_map(|(from, to)| vec![from.clone(), to.clone()])
+        .collect::<BTreeSet<String>>();
+    if !names.is_empty() {
+        return Err(RunError::Cycle {
+            names: names.into_iter().collect(),
+        });
+    }
+    let (checks, others): (Vec<&Item>, Vec<&Item>) = order
+        .into_iter()
+        .partition(|item| matches!(item, Item::DependencyCheck(_)));
+    Ok(checks.into_iter().chain(others).collect())
+}
+
+fn visit_item<'a>(
+    db: &Db<'a>,
+    item: &'a Item,
+    order: &mut Vec<&'a Item>,
+    seen: &mut HashSet<String>,
+) -> Result<(), RunError> {
+    if !seen.insert(item.name()) {
+        return Ok(());
+    }
+    match item {
+        Item::Topic(topic) => {
+            for item_wrap in topic.deps.iter().chain(topic.steps.iter()) {
+                visit_item_wrap(db, item, item_wrap, order, seen)?;
+            }
+        }
+        Item::TaskGroup(tg) => {
+            for item_wrap in &tg.steps {
+                visit_item_wrap(db, item, item_wrap, order, seen)?;
+            }
+        }
+        _ => order.push(item),
+    }
+    Ok(())
+}
+
+fn visit_item_wrap<'a>(
+    db: &Db<'a>,
+    parent: &'a Item,
+    item_wrap: &'a ItemWrap,
+    order: &mut Vec<&'a Item>,
+    seen: &mut HashSet<String>,
+) -> Result<(), RunError> {
+    match item_wrap {
+        ItemWrap::NamedRef(line_marker) => match db.get(&line_marker.item) {
+            Some((_, item)) => visit_item(db, *item, order, seen),
+            None => Err(RunError::MissingRef {
+                parent: parent.name(),
+                reference: line_marker.item.clone(),
+            }),
+        },
+        ItemWrap::Item(item) => visit_item(db, item, order, seen),
+    }
+}
+
+///
+/// Execute each item in turn, stopping at the first failure.
+///
+/// Output from each process is streamed straight to the terminal
+///
+pub fn execute(db: &Db, items: &[&Item], ctx: &Context) -> Result<usize, RunError> {
+    let total = items.len();
+    for (index, item) in items.iter().enumerate() {
+        print_step(index + 1, total, item);
+        let failed = |reason: String| RunError::Failed {
+            kind: item.kind_name(),
+            name: item.name(),
+            reason,
+            completed: index,
+            total,
+        };
+        let spawn_err = |original: std::io::Error| RunError::Spawn {
+            kind: item.kind_name(),
+            name: item.name(),
+            original,
+        };
+        match item {
+            Item::DependencyCheck(dc) => {
+                let status = sh(&dc.verify)
+                    .current_dir(ctx.join_path(dc.verify_cwd.0.clone()))
+                    .status();
+                check_status(status.map_err(spawn_err)?).map_err(failed)?;
+            }
+            Item::Command(cmd) => {
+                let mut command = sh(&cmd.interpolated(config_lookup(db.item_lookup)));
+                command.current_dir(ctx.join_path(cmd.cwd.0.clone()));
+                command.envs(cmd.env_values(config_lookup(db.item_lookup)));
+                check_status(command.status().map_err(spawn_err)?).map_err(failed)?;
+            }
+            Item::FileExistsCheck(fec) => {
+                let pb = ctx.join_path(fec.cwd.join(&fec.path));
+                if !pb.exists() {
+                    return Err(failed(format!("{} does not exist", pb.display())));
+                }
+            }
+            Item::Instruction(inst) => {
+                let body = db
+                    .get(&inst.name.item)
+                    .map(|(src, _)| instruction_body(*src, inst))
+                    .unwrap_or_default();
+                if !body.trim().is_empty() {
+                    println!("{}", body.trim_end());
+                }
+            }
+            Item::HostEntriesCheck(hec) => {
+                let hosts = std::fs::read_to_string(HOSTS_FILE)
+                    .map_err(|e| failed(format!("{} could not be read: {}", HOSTS_FILE, e)))?;
+                let missing = hec.missing(&hosts);
+                if !missing.is_empty() {
+                    return Err(failed(format!(
+                        "`{}` missing from {}",
+                        missing.join("`, `"),
+                        HOSTS_FILE
+                    )));
+                }
+            }
+            // these are expanded by `run_order`, so can never be reached
+            Item::Topic(_) | Item::TaskGroup(_) => {}
+            // only ever read by Commands
+            Item::Config(_) => {}
+        }
+    }
+    Ok(total)
+}
+
+pub(crate) fn sh(script: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(script);
+    command
+}
+
+pub(crate) fn check_status(status: ExitStatus) -> Result<(), String> {
+    match (status.success(), status.code()) {
+        (true, _) => Ok(()),
+        (false, Some(code)) => Err(format!("exit code {}", code)),
+        (false, None) => Err(String::from("terminated by a signal")),
+    }
+}
+
+fn print_step(num: usize, total: usize, item: &Item) {
+    use ansi_term::Colour::{Cyan, Green};
+    eprintln!();
+    eprintln!(
+        "{} {}: {}",
+        Green.bold().paint(format!("[{}/{}]", num, total)),
+        item.kind_name(),
+        Cyan.paint(item.name())
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::with_db;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_run_order() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/run/topics.yaml")]);
+        let names = with_db(&f, |db, _errors| {
+            run_order(db, "Run the tests")
+                .expect("run_order")
+                .iter()
+                .map(|item| item.name())
+                .collect::<Vec<String>>()
+        });
+        assert_eq!(
+            names,
+            vec![
+                "shell available",
+                "print hello",
+                "fail on purpose",
+                "never runs"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_topic_not_found() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/run/topics.yaml")]);
+        let message = with_db(&f, |db, _errors| match run_order(db, "run the tests") {
+            Err(err @ RunError::TopicNotFound { .. }) => err.to_string(),
+            _ => unreachable!(),
+        });
+        assert_eq!(
+            message,
+            "Topic `run the tests` was not found, did you mean `Run the tests`?"
+        );
+    }
+
+    #[test]
+    fn test_execute_stops_at_first_failure() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/run/topics.yaml")]);
+        let result = with_db(&f, |db, _errors| {
+            let items = run_order(db, "Run the tests").expect("run_order");
+            execute(db, &items, &ctx)
+        });
+        match result {
+            Err(RunError::Failed {
+                name,
+                reason,
+                completed,
+                total,
+                ..
+            }) => {
+                assert_eq!(name, "fail on purpose");
+                assert_eq!(reason, "exit code 3");
+                assert_eq!((completed, total), (2, 4));
+            }
+            _ => unreachable!(),
+        }
+    }
+}
@@ -1,89 +1,450 @@
 use conch_parser::ast::{
-    AndOrList, Arithmetic, Command, ComplexWord, CompoundCommand, CompoundCommandKind,
-    ListableCommand, Parameter, ParameterSubstitution, PipeableCommand, Redirect,
-    RedirectOrCmdWord, SimpleCommand, SimpleWord, TopLevelCommand, TopLevelWord, Word,
+    AndOr, Arithmetic, Command, ComplexWord, CompoundCommandKind, ListableCommand, Parameter,
+    ParameterSubstitution, PipeableCommand, Redirect, RedirectOrCmdWord, RedirectOrEnvVar,
+    SimpleWord, TopLevelCommand, TopLevelWord, Word,
 };
 use conch_parser::lexer::Lexer;
-use conch_parser::parse::DefaultParser;
-use std::rc::Rc;
-
-fn main() {
-    // Initialize our token lexer and shell parser with the program's input
-    let cmd = r#"
-DEBUG=true echo foo ${BAR}
-if []
-    "#;
-    let lex = Lexer::new(cmd.chars());
-    let parser = DefaultParser::new(lex);
-
-    // Parse our input!
-    for t in parser {
-        match t {
-            Err(e) => {
-                eprintln!("ERROR");
-                eprintln!("{:?}", e);
-                eprintln!("{}", e.to_string());
-            }
-            Ok(TopLevelCommand(tlc)) => match tlc {
-                Command::Job(_) => {}
-                Command::List(list) => match list {
-                    AndOrList { first, rest } => {
-                        println!("rest={:?}", rest);
-                        match first {
-                            ListableCommand::Pipe(_, _) => {}
-                            ListableCommand::Single(single) => match single {
-                                PipeableCommand::Simple(simple) => {
-                                    for w in simple.redirects_or_cmd_words {
-                                        match w {
-                                            RedirectOrCmdWord::Redirect(_) => {}
-                                            RedirectOrCmdWord::CmdWord(cmd_word) => {
-                                                match cmd_word {
-                                                    TopLevelWord(tlw) => {
-                                                        println!("tlw={:?}", tlw);
-                                                        match tlw {
-                                                            ComplexWord::Concat(_) => {}
-                                                            ComplexWord::Single(single) => match single {
-                                                                Word::Simple(w_s) => match w_s {
-                                                                    SimpleWord::Literal(_) => {}
-                                                                    SimpleWord::Escaped(_) => {}
-                                                                    SimpleWord::Param(p) => match p {
-                                                                        Parameter::At => {}
-                                                                        Parameter::Star => {}
-                                                                        Parameter::Pound => {}
-                                                                        Parameter::Question => {}
-                                                                        Parameter::Dash => {}
-                                                                        Parameter::Dollar => {}
-                                                                        Parameter::Bang => {}
-                                                                        Parameter::Positional(_) => {}
-                                                                        Parameter::Var(var) => {
-                                                                            println!("var={}", var);
-                                                                        }
-                                                                    },
-                                                                    SimpleWord::Subst(_) => {}
-                                                                    SimpleWord::Star => {}
-                                                                    SimpleWord::Question => {}
-                                                                    SimpleWord::SquareOpen => {}
-                                                                    SimpleWord::SquareClose => {}
-                                                                    SimpleWord::Tilde => {}
-                                                                    SimpleWord::Colon => {}
-                                                                },
-                                                                Word::DoubleQuoted(_) => {}
-                                                                Word::SingleQuoted(_) => {}
-                                                            },
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                                PipeableCommand::Compound(_) => {}
-                                PipeableCommand::FunctionDef(_, _) => {}
-                            },
+use conch_parser::parse::{DefaultParser, ParseError};
+use std::collections::BTreeSet;
+
+///
+/// Variables that are always available in a shell, or that come from the user's
+/// own environment, so they're never reported as missing
+///
+const ENVIRONMENT: &[&str] = &[
+    "HOME", "HOSTNAME", "IFS", "LANG", "LINENO", "OLDPWD", "PATH", "PPID", "PWD", "RANDOM",
+    "SECONDS", "SHELL", "TERM", "TMPDIR", "UID", "USER",
+];
+
+///
+/// Built-in to the shell, so these are never looked up on the `PATH`
+///
+const BUILTINS: &[&str] = &[
+    ".", ":", "[", "alias", "bg", "break", "cd", "command", "continue", "declare", "echo", "eval",
+    "exec", "exit", "export", "false", "fg", "getopts", "hash", "jobs", "local", "printf", "pwd",
+    "read", "readonly", "return", "set", "shift", "source", "test", "times", "trap", "true",
+    "type", "ulimit", "umask", "unset", "wait",
+];
+
+///
+/// Everything that could be learned about a script without running it
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShellAnalysis {
+    /// every variable the script reads without providing a default
+    pub vars: BTreeSet<String>,
+    /// variables given a value within the script itself, eg: `NAME=value` or `for NAME in ...`
+    pub assigned: BTreeSet<String>,
+    /// programs the script invokes, excluding shell built-ins + functions it defines
+    pub executables: BTreeSet<String>,
+    /// the first syntax error, anything after it was not analyzed
+    pub syntax_error: Option<SyntaxError>,
+}
+
+///
+/// A syntax error within a script. `line` is relative to the script, starting at 1
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub message: String,
+    pub line: u32,
+}
+
+impl ShellAnalysis {
+    ///
+    /// Variables that are read but not assigned by the script, not part of the
+    /// usual environment, and not `supplied` from elsewhere (eg: a Command's Env)
+    ///
+    pub fn unset_vars(&self, supplied: impl Fn(&str) -> bool) -> Vec<String> {
+        self.vars
+            .iter()
+            .filter(|var| !self.assigned.contains(*var))
+            .filter(|var| !ENVIRONMENT.contains(&var.as_str()))
+            .filter(|var| !supplied(var))
+            .cloned()
+            .collect()
+    }
+}
+
+///
+/// Parse a script and walk its AST, collecting variables + executables
+///
+/// ```rust
+/// use topics_core::shell::analyze;
+///
+/// let analysis = analyze("GREETING=hello\necho ${GREETING} ${NAME} | tee out.txt");
+/// assert_eq!(analysis.unset_vars(|_| false), vec![String::from("NAME")]);
+/// assert!(analysis.executables.contains("tee"));
+/// assert_eq!(analysis.syntax_error, None);
+/// ```
+pub fn analyze(script: &str) -> ShellAnalysis {
+    let mut analysis = ShellAnalysis::default();
+    let mut functions = BTreeSet::new();
+    let parser = DefaultParser::new(Lexer::new(script.chars()));
+    for result in parser {
+        match result {
+            Ok(top_level) => visit_command(&top_level, &mut analysis, &mut functions),
+            Err(err) => {
+                analysis.syntax_error = Some(syntax_error(err, script));
+                break;
+            }
+        }
+    }
+    for name in &functions {
+        analysis.executables.remove(name);
+    }
+    analysis
+}
+
+fn syntax_error<T>(err: ParseError<T>, script: &str) -> SyntaxError {
+    let (message, pos) = match err {
+        ParseError::Unexpected(token, pos) => (format!("unexpected `{}`", token), pos),
+        ParseError::Unmatched(token, pos) => (format!("unmatched `{}`", token), pos),
+        ParseError::BadSubst(token, pos) => (format!("bad substitution `{}`", token), pos),
+        ParseError::BadIdent(ident, pos) => (format!("bad identifier `{}`", ident), pos),
+        ParseError::BadFd(pos, _) => (String::from("bad file descriptor"), pos),
+        ParseError::IncompleteCmd(cmd, pos, keyword, _) => (
+            format!("`{}` is missing a matching `{}`", cmd, keyword),
+            pos,
+        ),
+        ParseError::UnexpectedEOF | ParseError::Custom(_) => {
+            return SyntaxError {
+                message: String::from("unexpected end of script"),
+                line: script.lines().count().max(1) as u32,
+            }
+        }
+    };
+    SyntaxError {
+        message,
+        line: pos.line as u32,
+    }
+}
+
+fn visit_command(
+    top_level: &TopLevelCommand<String>,
+    analysis: &mut ShellAnalysis,
+    functions: &mut BTreeSet<String>,
+) {
+    let list = match &top_level.0 {
+        Command::Job(list) | Command::List(list) => list,
+    };
+    visit_listable(&list.first, analysis, functions);
+    for and_or in &list.rest {
+        match and_or {
+            AndOr::And(cmd) | AndOr::Or(cmd) => visit_listable(cmd, analysis, functions),
+        }
+    }
+}
+
+type ShellPipeable =
+    conch_parser::ast::ShellPipeableCommand<String, TopLevelWord<String>, TopLevelCommand<String>>;
+
+fn visit_listable(
+    listable: &ListableCommand<ShellPipeable>,
+    analysis: &mut ShellAnalysis,
+    functions: &mut BTreeSet<String>,
+) {
+    match listable {
+        ListableCommand::Single(cmd) => visit_pipeable(cmd, analysis, functions),
+        ListableCommand::Pipe(_, cmds) => {
+            for cmd in cmds {
+                visit_pipeable(cmd, analysis, functions)
+            }
+        }
+    }
+}
+
+fn visit_pipeable(
+    pipeable: &ShellPipeable,
+    analysis: &mut ShellAnalysis,
+    functions: &mut BTreeSet<String>,
+) {
+    match pipeable {
+        PipeableCommand::Simple(simple) => {
+            for item in &simple.redirects_or_env_vars {
+                match item {
+                    RedirectOrEnvVar::Redirect(redirect) => visit_redirect(redirect, analysis),
+                    RedirectOrEnvVar::EnvVar(name, word) => {
+                        analysis.assigned.insert(name.clone());
+                        if let Some(word) = word {
+                            visit_word(word, analysis);
                         }
                     }
-                },
-            },
+                }
+            }
+            let mut words = vec![];
+            for item in &simple.redirects_or_cmd_words {
+                match item {
+                    RedirectOrCmdWord::Redirect(redirect) => visit_redirect(redirect, analysis),
+                    RedirectOrCmdWord::CmdWord(word) => {
+                        visit_word(word, analysis);
+                        words.push(word);
+                    }
+                }
+            }
+            let mut literals = words.into_iter().map(literal);
+            if let Some(Some(program)) = literals.next() {
+                visit_program(&program, literals.flatten(), analysis);
+            }
+        }
+        PipeableCommand::Compound(compound) => {
+            for redirect in &compound.io {
+                visit_redirect(redirect, analysis);
+            }
+            visit_compound(&compound.kind, analysis, functions);
+        }
+        PipeableCommand::FunctionDef(name, body) => {
+            functions.insert(name.clone());
+            for redirect in &body.io {
+                visit_redirect(redirect, analysis);
+            }
+            visit_compound(&body.kind, analysis, functions);
+        }
+    }
+}
+
+///
+/// Record the program being invoked, along with anything
+/// that it assigns, eg: `export NAME=value` or `read NAME`
+///
+fn visit_program(program: &str, args: impl Iterator<Item = String>, analysis: &mut ShellAnalysis) {
+    match program {
+        "export" | "local" | "readonly" | "declare" => {
+            for arg in args {
+                if let Some(index) = arg.find('=') {
+                    analysis.assigned.insert(arg[..index].to_string());
+                }
+            }
+        }
+        "read" => {
+            for arg in args.filter(|arg| !arg.starts_with('-')) {
+                analysis.assigned.insert(arg);
+            }
+        }
+        _ => {}
+    }
+    if !BUILTINS.contains(&program) {
+        analysis.executables.insert(program.to_string());
+    }
+}
+
+fn visit_compound(
+    kind: &CompoundCommandKind<String, TopLevelWord<String>, TopLevelCommand<String>>,
+    analysis: &mut ShellAnalysis,
+    functions: &mut BTreeSet<String>,
+) {
+    let mut visit_all = |cmds: &[TopLevelCommand<String>], analysis: &mut ShellAnalysis| {
+        for cmd in cmds {
+            visit_command(cmd, analysis, functions);
+        }
+    };
+    match kind {
+        CompoundCommandKind::Brace(cmds) | CompoundCommandKind::Subshell(cmds) => {
+            visit_all(cmds, analysis)
         }
+        CompoundCommandKind::While(pair) | CompoundCommandKind::Until(pair) => {
+            visit_all(&pair.guard, analysis);
+            visit_all(&pair.body, analysis);
+        }
+        CompoundCommandKind::If {
+            conditionals,
+            else_branch,
+        } => {
+            for pair in conditionals {
+                visit_all(&pair.guard, analysis);
+                visit_all(&pair.body, analysis);
+            }
+            if let Some(cmds) = else_branch {
+                visit_all(cmds, analysis);
+            }
+        }
+        CompoundCommandKind::For { var, words, body } => {
+            analysis.assigned.insert(var.clone());
+            for word in words.iter().flatten() {
+                visit_word(word, analysis);
+            }
+            visit_all(body, analysis);
+        }
+        CompoundCommandKind::Case { word, arms } => {
+            visit_word(word, analysis);
+            for arm in arms {
+                for pattern in &arm.patterns {
+                    visit_word(pattern, analysis);
+                }
+                visit_all(&arm.body, analysis);
+            }
+        }
+    }
+}
+
+fn visit_redirect(redirect: &Redirect<TopLevelWord<String>>, analysis: &mut ShellAnalysis) {
+    match redirect {
+        Redirect::Read(_, word)
+        | Redirect::Write(_, word)
+        | Redirect::ReadWrite(_, word)
+        | Redirect::Append(_, word)
+        | Redirect::Clobber(_, word)
+        | Redirect::Heredoc(_, word)
+        | Redirect::DupRead(_, word)
+        | Redirect::DupWrite(_, word) => visit_word(word, analysis),
+    }
+}
+
+type ShellSimpleWord =
+    conch_parser::ast::ShellSimpleWord<String, TopLevelWord<String>, TopLevelCommand<String>>;
+
+fn visit_word(word: &TopLevelWord<String>, analysis: &mut ShellAnalysis) {
+    let words = match &word.0 {
+        ComplexWord::Single(word) => vec![word],
+        ComplexWord::Concat(words) => words.iter().collect(),
+    };
+    for word in words {
+        match word {
+            Word::Simple(simple) => visit_simple_word(simple, analysis),
+            Word::DoubleQuoted(simples) => {
+                for simple in simples {
+                    visit_simple_word(simple, analysis);
+                }
+            }
+            Word::SingleQuoted(_) => {}
+        }
+    }
+}
+
+fn visit_simple_word(simple: &ShellSimpleWord, analysis: &mut ShellAnalysis) {
+    match simple {
+        SimpleWord::Param(Parameter::Var(name)) => {
+            analysis.vars.insert(name.clone());
+        }
+        SimpleWord::Subst(subst) => visit_subst(subst, analysis),
+        _ => {}
+    }
+}
+
+fn visit_subst(
+    subst: &ParameterSubstitution<
+        Parameter<String>,
+        TopLevelWord<String>,
+        TopLevelCommand<String>,
+        Arithmetic<String>,
+    >,
+    analysis: &mut ShellAnalysis,
+) {
+    let read = |param: &Parameter<String>, analysis: &mut ShellAnalysis| {
+        if let Parameter::Var(name) = param {
+            analysis.vars.insert(name.clone());
+        }
+    };
+    match subst {
+        ParameterSubstitution::Command(cmds) => {
+            let mut functions = BTreeSet::new();
+            for cmd in cmds {
+                visit_command(cmd, analysis, &mut functions);
+            }
+        }
+        // arithmetic is rare in scripts like these, so its variables are not checked
+        ParameterSubstitution::Arith(_) => {}
+        ParameterSubstitution::Len(param) => read(param, analysis),
+        // `${NAME:-default}` + `${NAME:+alternative}` are both fine when NAME is unset
+        ParameterSubstitution::Default(_, _, word)
+        | ParameterSubstitution::Alternative(_, _, word) => {
+            if let Some(word) = word {
+                visit_word(word, analysis);
+            }
+        }
+        ParameterSubstitution::Assign(_, param, word) => {
+            if let Parameter::Var(name) = param {
+                analysis.assigned.insert(name.clone());
+            }
+            if let Some(word) = word {
+                visit_word(word, analysis);
+            }
+        }
+        ParameterSubstitution::Error(_, param, word)
+        | ParameterSubstitution::RemoveSmallestSuffix(param, word)
+        | ParameterSubstitution::RemoveLargestSuffix(param, word)
+        | ParameterSubstitution::RemoveSmallestPrefix(param, word)
+        | ParameterSubstitution::RemoveLargestPrefix(param, word) => {
+            read(param, analysis);
+            if let Some(word) = word {
+                visit_word(word, analysis);
+            }
+        }
+    }
+}
+
+///
+/// The literal text of a word, or None if it contains any expansions
+///
+fn literal(word: &TopLevelWord<String>) -> Option<String> {
+    let words = match &word.0 {
+        ComplexWord::Single(word) => vec![word],
+        ComplexWord::Concat(words) => words.iter().collect(),
+    };
+    let mut output = String::new();
+    for word in words {
+        match word {
+            Word::SingleQuoted(text) => output.push_str(text),
+            Word::Simple(simple) => output.push_str(&literal_simple(simple)?),
+            Word::DoubleQuoted(simples) => {
+                for simple in simples {
+                    output.push_str(&literal_simple(simple)?);
+                }
+            }
+        }
+    }
+    Some(output)
+}
+
+fn literal_simple(simple: &ShellSimpleWord) -> Option<String> {
+    match simple {
+        SimpleWord::Literal(text) | SimpleWord::Escaped(text) => Some(text.clone()),
+        SimpleWord::Colon => Some(String::from(":")),
+        SimpleWord::SquareOpen => Some(String::from("[")),
+        SimpleWord::SquareClose => Some(String::from("]")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set(items: &[&str]) -> BTreeSet<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn test_analyze_vars_and_executables() {
+        let analysis = analyze(
+            r#"
+export NODE_ENV=production
+for file in $(ls "${SRC_DIR}"); do
+  node build.js "$file" --out=${OUT_DIR:-dist} > "$LOG_FILE"
+done
+if command -v docker; then docker build -t ${TAG} . ; fi
+echo "$HOME"
+"#,
+        );
+        assert_eq!(analysis.syntax_error, None);
+        assert_eq!(analysis.executables, set(&["docker", "ls", "node"]));
+        assert_eq!(
+            analysis.unset_vars(|name| name == "TAG"),
+            vec![String::from("LOG_FILE"), String::from("SRC_DIR")]
+        );
+    }
+
+    #[test]
+    fn test_functions_are_not_executables() {
+        let analysis = analyze("greet() { printf 'hi\\n'; }\ngreet && yarn");
+        assert_eq!(analysis.executables, set(&["yarn"]));
+    }
+
+    #[test]
+    fn test_syntax_error_line() {
+        let analysis = analyze("yarn install\ncd app\nyarn build )\n");
+        let err = analysis.syntax_error.expect("syntax error");
+        assert_eq!(err.line, 3);
+        assert_eq!(analysis.executables, set(&["yarn"]));
     }
 }
@@ -0,0 +1,325 @@
+use crate::context::Context;
+use crate::cwd::Cwd;
+use crate::db::Db;
+use crate::items::{DependencyCheck, Item};
+use crate::run::{check_status, run_order, sh};
+use crate::shell::analyze;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+///
+/// The result of verifying a single Dependency Check, and trying to fix it if asked
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyOutcome {
+    Passed,
+    Failed { reason: String },
+    Fixed,
+    StillBroken { reason: String },
+    NoFixAvailable { reason: String },
+    Declined { reason: String },
+}
+
+impl VerifyOutcome {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, VerifyOutcome::Passed | VerifyOutcome::Fixed)
+    }
+}
+
+impl Display for VerifyOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyOutcome::Passed => write!(f, "passed"),
+            VerifyOutcome::Failed { reason } => write!(f, "failed ({})", reason),
+            VerifyOutcome::Fixed => write!(f, "fixed"),
+            VerifyOutcome::StillBroken { reason } => {
+                write!(f, "still broken after autofix ({})", reason)
+            }
+            VerifyOutcome::NoFixAvailable { reason } => {
+                write!(f, "failed ({}), no autofix available", reason)
+            }
+            VerifyOutcome::Declined { reason } => {
+                write!(f, "failed ({}), autofix declined", reason)
+            }
+        }
+    }
+}
+
+///
+/// Every Dependency Check across all loaded documents, in the order they were defined
+///
+pub fn dependency_checks<'a>(db: &Db<'a>) -> Vec<&'a DependencyCheck> {
+    db.all_items()
+        .into_iter()
+        .filter_map(|item| match item {
+            Item::DependencyCheck(dc) => Some(dc),
+            _ => None,
+        })
+        .collect()
+}
+
+///
+/// Run the `verify` script of a Dependency Check.
+///
+/// When `confirm` is given, a failing check that has an `autofix` script will
+/// run it (if `confirm` agrees) and then be verified a second time
+///
+pub fn verify_check(
+    dc: &DependencyCheck,
+    ctx: &Context,
+    confirm: Option<&dyn Fn(&DependencyCheck) -> bool>,
+) -> VerifyOutcome {
+    let reason = match run_script(&dc.verify, &dc.verify_cwd, ctx) {
+        Ok(()) => return VerifyOutcome::Passed,
+        Err(reason) => reason,
+    };
+    let confirm = match confirm {
+        Some(confirm) => confirm,
+        None => return VerifyOutcome::Failed { reason },
+    };
+    let autofix = match &dc.autofix {
+        Some(autofix) => autofix,
+        None => return VerifyOutcome::NoFixAvailable { reason },
+    };
+    if !confirm(dc) {
+        return VerifyOutcome::Declined { reason };
+    }
+    // a failing autofix is only reported through the second verify
+    let _ = run_script(autofix, &dc.autofix_cwd, ctx);
+    match run_script(&dc.verify, &dc.verify_cwd, ctx) {
+        Ok(()) => VerifyOutcome::Fixed,
+        Err(reason) => VerifyOutcome::StillBroken { reason },
+    }
+}
+
+///
+/// A program that's invoked by at least 1 script, but can't be found on the `PATH`
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingBinary {
+    pub name: String,
+    /// the Commands + Dependency Checks whose scripts call it
+    pub used_by: Vec<String>,
+    /// every Topic that would run one of those items, directly or not
+    pub topics: Vec<String>,
+}
+
+impl Display for MissingBinary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let quoted = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        write!(
+            f,
+            "`{}` was not found on the PATH, it's used by {}",
+            self.name,
+            quoted(&self.used_by)
+        )?;
+        if !self.topics.is_empty() {
+            write!(f, ", which Topics {} need", quoted(&self.topics))?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Every program invoked by a Command or Dependency Check script, along
+/// with the items that invoke it. Shell built-ins + functions are not included
+///
+pub fn binaries<'a>(db: &Db<'a>) -> BTreeMap<String, Vec<&'a Item>> {
+    let mut output: BTreeMap<String, Vec<&'a Item>> = BTreeMap::new();
+    for item in db.all_items() {
+        let script = match item {
+            Item::Command(cmd) => &cmd.command,
+            Item::DependencyCheck(dc) => &dc.verify,
+            _ => continue,
+        };
+        for name in analyze(script).executables {
+            output.entry(name).or_insert_with(Vec::new).push(item);
+        }
+    }
+    output
+}
+
+///
+/// Look up every program from [`binaries`] within `path` (in the same format
+/// as the `PATH` environment variable), returning the ones that can't be found.
+///
+/// Names containing a `/` refer to files within the project rather than
+/// installed programs, so are left to File Exists Checks
+///
+pub fn missing_binaries(db: &Db, path: &OsStr) -> Vec<MissingBinary> {
+    let dirs = std::env::split_paths(path).collect::<Vec<_>>();
+    let topics = db
+        .items
+        .iter()
+        .flat_map(|(_, items)| items.iter())
+        .filter(|item| matches!(item, Item::Topic(_)))
+        .map(|topic| {
+            let needs = run_order(db, &topic.name()).unwrap_or_default();
+            (topic.name(), needs)
+        })
+        .collect::<Vec<(String, Vec<&Item>)>>();
+    binaries(db)
+        .into_iter()
+        .filter(|(name, _)| !name.contains('/'))
+        .filter(|(name, _)| !dirs.iter().any(|dir| is_executable(&dir.join(name))))
+        .map(|(name, items)| {
+            let used_by = items
+                .iter()
+                .map(|item| item.name())
+                .collect::<Vec<String>>();
+            let topics = topics
+                .iter()
+                .filter(|(_, needs)| needs.iter().any(|item| used_by.contains(&item.name())))
+                .map(|(topic, _)| topic.clone())
+                .collect();
+            MissingBinary {
+                name,
+                used_by,
+                topics,
+            }
+        })
+        .collect()
+}
+
+///
+/// A file that could be run as a program, on unix that means one of its executable bits is set
+///
+fn is_executable(pb: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        pb.metadata()
+            .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        pb.is_file()
+    }
+}
+
+fn run_script(script: &str, cwd: &Cwd, ctx: &Context) -> Result<(), String> {
+    let status = sh(script)
+        .current_dir(ctx.join_path(cwd.0.clone()))
+        .status()
+        .map_err(|e| e.to_string())?;
+    check_status(status)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::with_db;
+    use crate::opt::Opt;
+    use std::path::PathBuf;
+    use std::str::FromStr;
+
+    fn outcomes(fix: bool) -> Vec<(String, VerifyOutcome)> {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/verify/checks.yaml")]);
+
+        // scripts run in a fresh directory, so that autofix can leave a file behind
+        let dir =
+            std::env::temp_dir().join(format!("topics-verify-{}-{}", std::process::id(), fix));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let run_ctx = Context::from_opts(&Opt {
+            cwd: crate::cwd::Cwd::from_str(&dir.display().to_string()).expect("cwd"),
+            cmd: None,
+        });
+
+        let accept = |_: &DependencyCheck| true;
+        let confirm: Option<&dyn Fn(&DependencyCheck) -> bool> =
+            if fix { Some(&accept) } else { None };
+        let outcomes = with_db(&f, |db, _errors| {
+            dependency_checks(db)
+                .into_iter()
+                .map(|dc| (dc.name.item.clone(), verify_check(dc, &run_ctx, confirm)))
+                .collect::<Vec<(String, VerifyOutcome)>>()
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+        outcomes
+    }
+
+    #[test]
+    fn test_missing_binaries() {
+        let ctx = Context::default();
+        let f = ctx.read_docs_unwrapped(&[PathBuf::from("../fixtures/verify/binaries.yaml")]);
+
+        // a `PATH` that only contains `node`, `yarn` is there but can't be executed
+        let dir = std::env::temp_dir().join(format!("topics-binaries-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        std::fs::write(dir.join("node"), "").expect("write node");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::write(dir.join("yarn"), "").expect("write yarn");
+            let executable = std::fs::Permissions::from_mode(0o755);
+            std::fs::set_permissions(dir.join("node"), executable).expect("chmod node");
+        }
+        let missing = with_db(&f, |db, _errors| missing_binaries(db, dir.as_os_str()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            missing,
+            vec![
+                MissingBinary {
+                    name: String::from("skaffold"),
+                    used_by: vec![String::from("skaffold installed"), String::from("deploy")],
+                    topics: vec![String::from("Deploy"), String::from("Release")],
+                },
+                MissingBinary {
+                    name: String::from("yarn"),
+                    used_by: vec![String::from("build")],
+                    topics: vec![String::from("Release")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_without_fix() {
+        let outcomes = outcomes(false)
+            .into_iter()
+            .map(|(name, outcome)| (name, outcome.is_ok()))
+            .collect::<Vec<(String, bool)>>();
+        assert_eq!(
+            outcomes,
+            vec![
+                (String::from("passes"), true),
+                (String::from("fixable"), false),
+                (String::from("not fixable"), false),
+                (String::from("no autofix"), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_with_fix() {
+        let failed = String::from("exit code 1");
+        assert_eq!(
+            outcomes(true),
+            vec![
+                (String::from("passes"), VerifyOutcome::Passed),
+                (String::from("fixable"), VerifyOutcome::Fixed),
+                (
+                    String::from("not fixable"),
+                    VerifyOutcome::StillBroken {
+                        reason: failed.clone()
+                    }
+                ),
+                (
+                    String::from("no autofix"),
+                    VerifyOutcome::NoFixAvailable { reason: failed }
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use crate::cli::SubCommandResult;
+use crate::context::Context;
+use crate::doc::{Doc, DocResult};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+///
+/// How often files are checked for changes.
+///
+/// Polling keeps this free of platform specific file-system events, and
+/// is more than fast enough for a handful of hand-written documents
+///
+pub const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+#[derive(Debug)]
+struct WatchedFile {
+    pb: PathBuf,
+    modified: Option<SystemTime>,
+    doc: DocResult<Doc>,
+}
+
+///
+/// The parsed form of every watched file, where only files that changed
+/// since the last [`DocCache::refresh`] are read + parsed again
+///
+#[derive(Debug, Default)]
+pub struct DocCache {
+    files: Vec<PathBuf>,
+    entries: Vec<WatchedFile>,
+}
+
+impl DocCache {
+    pub fn new(files: &[PathBuf]) -> Self {
+        Self {
+            files: files.to_vec(),
+            entries: vec![],
+        }
+    }
+
+    ///
+    /// Re-parse any file whose modified time is different to last time,
+    /// returning the paths that changed (every path, on the first call)
+    ///
+    pub fn refresh(&mut self, ctx: &Context) -> Vec<PathBuf> {
+        let mut changed = vec![];
+        for pb in &self.files {
+            let modified = std::fs::metadata(ctx.join_path(pb))
+                .and_then(|meta| meta.modified())
+                .ok();
+            match self.entries.iter_mut().find(|entry| entry.pb == *pb) {
+                Some(entry) if entry.modified == modified => {}
+                Some(entry) => {
+                    entry.modified = modified;
+                    entry.doc = Doc::from_path_buf(pb, ctx);
+                    changed.push(pb.clone());
+                }
+                None => {
+                    self.entries.push(WatchedFile {
+                        pb: pb.clone(),
+                        modified,
+                        doc: Doc::from_path_buf(pb, ctx),
+                    });
+                    changed.push(pb.clone());
+                }
+            }
+        }
+        changed
+    }
+
+    ///
+    /// Call `f` with the documents split the same way as [`Context::read_docs_split`],
+    /// `good` ones first, followed by those that could not be read or had errors
+    ///
+    pub fn with_docs<T>(&mut self, f: impl FnOnce(&[Doc], &[DocResult<Doc>]) -> T) -> T {
+        let mut good = vec![];
+        let mut bad = vec![];
+        let mut slots = vec![];
+        for entry in std::mem::take(&mut self.entries) {
+            let is_good = matches!(&entry.doc, Ok(doc) if doc.errors.is_empty());
+            slots.push((entry.pb, entry.modified, is_good));
+            match entry.doc {
+                Ok(doc) if is_good => good.push(doc),
+                doc => bad.push(doc),
+            }
+        }
+        let output = f(&good, &bad);
+
+        // put everything back in its original order, ready for the next refresh
+        let mut good = good.into_iter();
+        let mut bad = bad.into_iter();
+        self.entries = slots
+            .into_iter()
+            .map(|(pb, modified, is_good)| WatchedFile {
+                pb,
+                modified,
+                doc: if is_good {
+                    Ok(good.next().expect("one good doc per slot"))
+                } else {
+                    bad.next().expect("one bad doc per slot")
+                },
+            })
+            .collect();
+        output
+    }
+}
+
+///
+/// Block forever, calling `cycle` with the latest documents every time a watched file changes.
+///
+/// The screen is cleared before each cycle (when stderr is a terminal), and `cycle` returns the summary that's printed after it
+///
+pub fn watch(
+    files: &[PathBuf],
+    ctx: &Context,
+    mut cycle: impl FnMut(&[Doc], &[DocResult<Doc>]) -> String,
+) {
+    use ansi_term::Colour::{Cyan, Green};
+    let mut cache = DocCache::new(files);
+    let mut count = 0;
+    loop {
+        let changed = cache.refresh(ctx);
+        if !changed.is_empty() {
+            count += 1;
+            // clear the screen + move the cursor back to the top, stdout is left
+            // alone so that it only ever contains the output of `cycle`
+            if atty::is(atty::Stream::Stderr) {
+                eprint!("\x1B[2J\x1B[1;1H");
+            }
+            let summary = cache.with_docs(&mut cycle);
+            eprintln!();
+            eprintln!(
+                "{} {} file(s) changed, {} - waiting for changes to {} file(s)",
+                Green.bold().paint(format!("[Watch #{}]", count)),
+                changed.len(),
+                summary,
+                Cyan.paint(files.len().to_string())
+            );
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+///
+/// The summary for a single cycle of a sub-command, the details are printed as it runs
+///
+pub fn summary(result: &SubCommandResult<()>) -> String {
+    use ansi_term::Colour::{Green, Red};
+    match result {
+        Ok(()) => Green.paint("no problems found").to_string(),
+        Err(_) => Red.paint("problems found, see above").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_only_changed_files_are_parsed_again() {
+        let dir = std::env::temp_dir().join(format!("topics-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let yaml = dir.join("topics.yaml");
+        let md = dir.join("topics.md");
+        std::fs::write(&yaml, "kind: Command\nname: a\ncommand: echo a\n").expect("write");
+        std::fs::write(&md, "# Command: b\n\n```shell\necho b\n```\n").expect("write");
+
+        let ctx = Context::default();
+        let mut cache = DocCache::new(&[yaml.clone(), md.clone()]);
+        assert_eq!(cache.refresh(&ctx), vec![yaml.clone(), md.clone()]);
+        assert_eq!(cache.refresh(&ctx), Vec::<PathBuf>::new());
+
+        // make sure the new modified time can't be the same as the first
+        std::thread::sleep(Duration::from_millis(20));
+        std::fs::write(&yaml, "kind: Command\nname: a\ncommand: echo a2\n").expect("write");
+        assert_eq!(cache.refresh(&ctx), vec![yaml.clone()]);
+
+        let (good, bad) = cache.with_docs(|good, bad| (good.len(), bad.len()));
+        assert_eq!((good, bad), (2, 0));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}